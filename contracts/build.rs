@@ -17,51 +17,9 @@ fn main() {
 
     generate_contract("ERC20");
     generate_contract("ERC20Mintable");
+    // Deployments of the EasyAuction contract are configured in orderbook/networks.json
     generate_contract_with_config("EasyAuction", |builder| {
-        builder
-            .with_contract_mod_override(Some("easy_auction"))
-            .add_deployment(
-                1,
-                addr("0b7fFc1f4AD541A4Ed16b40D8c37f0929158D101"),
-                Some(tx(
-                    "0xa7ad659a9762720bd86a30b49a3e139928cc2a27d0863ab78110e19d2bef8a51",
-                )),
-            )
-            .add_deployment(
-                4,
-                addr("C5992c0e0A3267C7F75493D0F717201E26BE35f7"),
-                Some(tx(
-                    "0xbdd1dde815a908d407ec89fa9bc317d9e33621ccc6452ac0eb00fe2ed0d81ff4",
-                )),
-            )
-            .add_deployment(
-                5,
-                addr("0x1fbab40c338e2e7243da945820ba680c92ef8281"),
-                Some(tx(
-                    "0x6cbf82cec76ea4800d51150478fce1fbfb2284e450624489fbe3dbd4324fcc4b",
-                )),
-            )
-            .add_deployment(
-                100,
-                addr("0b7fFc1f4AD541A4Ed16b40D8c37f0929158D101"),
-                Some(tx(
-                    "0x5af5443ba9add113a42b0219ac8f398c383dc5a3684a221fd24c5655b8316931",
-                )),
-            )
-            .add_deployment(
-                137,
-                addr("0b7fFc1f4AD541A4Ed16b40D8c37f0929158D101"),
-                Some(tx(
-                    "0x6093f70c46350202181e9b0edfcf8f0e966ddddeb8b24e8b73dd2ab636c1ce87",
-                )),
-            )
-            .add_deployment(
-                43114,
-                addr("0xb5D00F83680ea5E078e911995c64b43Fbfd1eE61"),
-                Some(tx(
-                    "0xa6fa39783a488c892f28ce75ec2d8d079fb8d7ac4647c09bed9755e4246fd390",
-                )),
-            )
+        builder.with_contract_mod_override(Some("easy_auction"))
     });
     generate_contract_with_config("AllowListOffChainManaged", |builder| {
         builder
@@ -127,6 +85,12 @@ fn main() {
                 )),
             )
     });
+    generate_contract_with_config("UniswapV2Pair", |builder| {
+        builder.with_contract_mod_override(Some("uniswap_v2_pair"))
+    });
+    generate_contract_with_config("UniswapV3Pool", |builder| {
+        builder.with_contract_mod_override(Some("uniswap_v3_pool"))
+    });
     generate_contract_with_config("WETH9", |builder| {
         builder
             // Rinkeby & Mainnet Addresses are part of the artefact
@@ -35,6 +35,14 @@ const NPM_CONTRACTS: &[(&str, &str)] = &[
         "canonical-weth@1.4.0/build/contracts/WETH9.json",
         "WETH9.json",
     ),
+    (
+        "@uniswap/v2-core@1.0.1/build/UniswapV2Pair.json",
+        "UniswapV2Pair.json",
+    ),
+    (
+        "@uniswap/v3-core@1.0.0/artifacts/contracts/UniswapV3Pool.sol/UniswapV3Pool.json",
+        "UniswapV3Pool.json",
+    ),
 ];
 
 fn main() {
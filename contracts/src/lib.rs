@@ -6,4 +6,6 @@ include!(concat!(env!("OUT_DIR"), "/ERC20Mintable.rs"));
 include!(concat!(env!("OUT_DIR"), "/EasyAuction.rs"));
 include!(concat!(env!("OUT_DIR"), "/AllowListOffChainManaged.rs"));
 include!(concat!(env!("OUT_DIR"), "/DepositAndPlaceOrder.rs"));
+include!(concat!(env!("OUT_DIR"), "/UniswapV2Pair.rs"));
+include!(concat!(env!("OUT_DIR"), "/UniswapV3Pool.rs"));
 include!(concat!(env!("OUT_DIR"), "/WETH9.rs"));
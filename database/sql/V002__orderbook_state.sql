@@ -0,0 +1,28 @@
+CREATE TABLE auctions (
+    chain_id bigint NOT NULL,
+    auction_id bigint NOT NULL,
+    details jsonb NOT NULL,
+    PRIMARY KEY (chain_id, auction_id)
+);
+
+CREATE TABLE orders (
+    chain_id bigint NOT NULL,
+    auction_id bigint NOT NULL,
+    encoded_order bytea NOT NULL, -- user_id + buy_amount + sell_amount
+    claimed boolean NOT NULL DEFAULT false,
+    PRIMARY KEY (chain_id, auction_id, encoded_order)
+);
+
+CREATE TABLE users (
+    chain_id bigint NOT NULL,
+    user_id bigint NOT NULL,
+    user_address bytea NOT NULL,
+    PRIMARY KEY (chain_id, user_id)
+);
+
+-- Last block whose events are fully reflected in the tables above.
+CREATE TABLE sync_cursor (
+    chain_id bigint NOT NULL,
+    last_block bigint NOT NULL,
+    PRIMARY KEY (chain_id)
+);
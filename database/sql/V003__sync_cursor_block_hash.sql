@@ -0,0 +1,3 @@
+-- Hash of the block referenced by the cursor, used to detect reorgs after restarts.
+-- NULL for cursors written before block hashes were tracked.
+ALTER TABLE sync_cursor ADD COLUMN block_hash bytea;
@@ -0,0 +1,12 @@
+-- Signatures of auctions on different chains are stored in the same table. Until now, a
+-- database only contained the orderbook of a single chain, hence the signatures belong to
+-- the chain that is synced into the database.
+ALTER TABLE signatures ADD COLUMN chain_id bigint;
+
+UPDATE signatures SET chain_id = COALESCE((SELECT min(chain_id) FROM sync_cursor), 0);
+
+ALTER TABLE signatures ALTER COLUMN chain_id SET NOT NULL;
+
+ALTER TABLE signatures DROP CONSTRAINT signatures_pkey;
+
+ALTER TABLE signatures ADD PRIMARY KEY (chain_id, auction_id, user_address);
@@ -0,0 +1,8 @@
+-- USD prices of whole tokens, by which auctions are valued at their end.
+CREATE TABLE token_prices (
+    chain_id bigint NOT NULL,
+    token bytea NOT NULL,
+    day bigint NOT NULL, -- unix timestamp of the start of the day
+    price numeric NOT NULL,
+    PRIMARY KEY (chain_id, token, day)
+);
@@ -0,0 +1,10 @@
+-- Auctions, whose details could not be read yet, see orderbook/src/dead_letters.rs.
+CREATE TABLE dead_letters (
+    chain_id bigint NOT NULL,
+    auction_id bigint NOT NULL,
+    block_number bigint NOT NULL,
+    error text NOT NULL,
+    attempts bigint NOT NULL,
+    next_retry bigint NOT NULL,
+    PRIMARY KEY (chain_id, auction_id)
+);
@@ -0,0 +1,11 @@
+-- Clearing price and bidding amount of an auction after the events of a block, recorded for the
+-- blocks with events of the auction.
+CREATE TABLE clearing_prices (
+    chain_id bigint NOT NULL,
+    auction_id bigint NOT NULL,
+    block_number bigint NOT NULL,
+    timestamp bigint NOT NULL, -- unix timestamp of the block
+    clearing_price numeric NOT NULL,
+    bidding_amount numeric NOT NULL,
+    PRIMARY KEY (chain_id, auction_id, block_number)
+);
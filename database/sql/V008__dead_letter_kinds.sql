@@ -0,0 +1,3 @@
+-- Dead letters are also written for the clearing of indexed auctions, in which case block_number is
+-- the block of the clearing event.
+ALTER TABLE dead_letters ADD COLUMN kind text NOT NULL DEFAULT 'creation';
@@ -0,0 +1,10 @@
+-- Updates of each synced block range, stored together with the hash of its last block. After a
+-- reorg, also one detected after a restart, the persisted state is reverted to the last range whose
+-- block is still canonical, see orderbook/src/orderbook.rs.
+CREATE TABLE sync_checkpoints (
+    chain_id bigint NOT NULL,
+    block_number bigint NOT NULL, -- last block of the range
+    block_hash bytea NOT NULL,
+    updates jsonb NOT NULL,
+    PRIMARY KEY (chain_id, block_number)
+);
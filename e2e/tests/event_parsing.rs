@@ -1,18 +1,20 @@
 use contracts::{ERC20Mintable, EasyAuction};
 use ethcontract::prelude::{Account, Address, BlockNumber, U256};
+use model::decimal::Decimal;
 use model::order::PricePoint;
 use orderbook::database::Database;
 use orderbook::event_reader::EventReader;
 use orderbook::health::HttpHealthEndpoint;
 use orderbook::orderbook::{Orderbook, QUEUE_START};
-use orderbook::subgraph::uniswap_graph_api::UniswapSubgraphClient;
+use orderbook::price_oracle::PriceOracles;
+use orderbook::ChainOrderbook;
 use serde_json::Value;
 use std::{str::FromStr, sync::Arc};
 
 mod ganache;
 
 const API_HOST: &str = "http://127.0.0.1:8080";
-const ORDERBOOK_DISPLAY_ENDPOINT: &str = "/api/v1/get_order_book_display_data/";
+const ORDERBOOK_DISPLAY_ENDPOINT: &str = "get_order_book_display_data/";
 
 #[tokio::test(flavor = "current_thread")]
 async fn event_parsing() {
@@ -119,15 +121,18 @@ async fn event_parsing() {
         let database = Database::new("postgresql://").expect("failed to create database");
         database.clear().await.unwrap();
         let health = Arc::new(HttpHealthEndpoint::new());
+        let event_reader = EventReader::new(easy_auction, web3, 100u64);
         orderbook::serve_task(
-            orderbook.clone(),
+            vec![ChainOrderbook {
+                chain_id: chain_id.as_u32(),
+                orderbook: orderbook.clone(),
+                health,
+                metrics: event_reader.metrics.clone(),
+            }],
             database,
-            health,
             API_HOST[7..].parse().expect("Couldn't parse API address"),
         );
-        let event_reader = EventReader::new(easy_auction, web3, 100u64);
         let mut last_block_considered = 1u64;
-        let mut the_graph_reader = UniswapSubgraphClient::for_chain(1).unwrap();
         let current_block = event_reader
             .web3
             .eth()
@@ -138,11 +143,11 @@ async fn event_parsing() {
         orderbook::orderbook::Orderbook::run_maintenance(
             &orderbook,
             &event_reader,
-            &mut the_graph_reader,
+            &PriceOracles::default(),
             &mut last_block_considered,
-            false,
             chain_id.as_u32(),
             current_block,
+            None,
         )
         .await
         .unwrap();
@@ -150,8 +155,8 @@ async fn event_parsing() {
 
         let orderbook_display = client
             .get(&format!(
-                "{}{}{}",
-                API_HOST, ORDERBOOK_DISPLAY_ENDPOINT, auction_id
+                "{}/api/v1/{}/{}{}",
+                API_HOST, chain_id, ORDERBOOK_DISPLAY_ENDPOINT, auction_id
             ))
             .send()
             .await
@@ -161,28 +166,29 @@ async fn event_parsing() {
         let expected_price_point = PricePoint {
             price: 2.0_f64,
             volume: 2.0_f64,
+            exact_price: Decimal::from_str("2").unwrap(),
+            exact_volume: Decimal::from_str("2").unwrap(),
         };
         let bids: Vec<PricePoint> =
             serde_json::from_value(orderbook_value["bids"].clone()).unwrap();
         assert_eq!(bids, vec![expected_price_point]);
-        let mut the_graph_reader = UniswapSubgraphClient::for_chain(1).unwrap();
 
         //rerunning the maintenance function should not change the result
         orderbook::orderbook::Orderbook::run_maintenance(
             &orderbook,
             &event_reader,
-            &mut the_graph_reader,
+            &PriceOracles::default(),
             &mut last_block_considered,
-            false,
             chain_id.as_u32(),
             current_block,
+            None,
         )
         .await
         .unwrap();
         let orderbook_display = client
             .get(&format!(
-                "{}{}{}",
-                API_HOST, ORDERBOOK_DISPLAY_ENDPOINT, auction_id
+                "{}/api/v1/{}/{}{}",
+                API_HOST, chain_id, ORDERBOOK_DISPLAY_ENDPOINT, auction_id
             ))
             .send()
             .await
@@ -1,3 +1,4 @@
+use super::decimal::Decimal;
 use super::order::{Order, PricePoint};
 use ethcontract::Address;
 use primitive_types::U256;
@@ -13,6 +14,14 @@ pub struct AuctionDetails {
     pub exact_order: Order,
     pub symbol_auctioning_token: String,
     pub symbol_bidding_token: String,
+    #[serde(default)]
+    pub name_auctioning_token: String,
+    #[serde(default)]
+    pub name_bidding_token: String,
+    #[serde(default)]
+    pub logo_uri_auctioning_token: Option<String>,
+    #[serde(default)]
+    pub logo_uri_bidding_token: Option<String>,
     pub address_auctioning_token: Address,
     pub address_bidding_token: Address,
     pub decimals_auctioning_token: U256,
@@ -24,19 +33,222 @@ pub struct AuctionDetails {
     pub min_funding_threshold: U256,
     pub allow_list_manager: Address,
     pub allow_list_signer: Address,
+    /// Lossy approximation of exact_current_clearing_price, only kept for backward
+    /// compatibility.
     pub current_clearing_price: f64,
+    #[serde(default)]
+    pub exact_current_clearing_price: Decimal,
     pub current_bidding_amount: U256,
     pub is_atomic_closure_allowed: bool,
     pub is_private_auction: bool,
     pub chain_id: U256,
     pub interest_score: f64,
+    /// Lossy approximation of exact_usd_amount_traded, only kept for backward
+    /// compatibility.
     pub usd_amount_traded: f64,
+    #[serde(default)]
+    pub exact_usd_amount_traded: Decimal,
+    #[serde(flatten)]
+    pub settlement: AuctionSettlement,
+    /// Status at the time of the last synced block.
+    #[serde(default)]
+    pub status: AuctionStatus,
+}
+
+/// Final result of an auction, as emitted by the `AuctionCleared` event.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AuctionSettlement {
+    pub is_settled: bool,
+    pub clearing_price_order: Order,
+    pub volume_clearing_price_order: U256,
+    pub sold_auctioning_tokens: U256,
+    pub sold_bidding_tokens: U256,
+    pub settlement_block: u64,
+    /// Whether the auction was settled by `settleAuctionAtomically`, which places a last bid
+    /// in the same transaction.
+    pub is_atomically_closed: bool,
+}
+
+/// Clearing price and bidding amount of an auction after the events of a block.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearingPricePoint {
+    pub block_number: u64,
+    pub timestamp: u64,
+    pub clearing_price: Decimal,
+    /// Sum of the bids considered for the clearing, in bidding tokens.
+    pub bidding_amount: Decimal,
 }
 
 impl AuctionDetails {
     pub fn bidding_volume(&self) -> f64 {
         self.order.volume * self.order.price
     }
+
+    /// Point of the given block in the price history, with the clearing order and the
+    /// bidding amount after the events of the block. `None` if the clearing price can not be
+    /// represented.
+    pub fn clearing_price_point(
+        &self,
+        block_number: u64,
+        timestamp: u64,
+        clearing_order: &Order,
+        bidding_amount: U256,
+    ) -> Option<ClearingPricePoint> {
+        let clearing_price = clearing_order
+            .convert_to_price_point(self.decimals_auctioning_token, self.decimals_bidding_token)?
+            .exact_price;
+        Some(ClearingPricePoint {
+            block_number,
+            timestamp,
+            clearing_price,
+            bidding_amount: Decimal::new(bidding_amount, self.decimals_bidding_token.as_u32()),
+        })
+    }
+
+    /// Status of the auction at the given block timestamp, considering the indexed bids and
+    /// settlement.
+    pub fn status_at(&self, timestamp: u64) -> AuctionStatus {
+        let settlement = &self.settlement;
+        if settlement.is_settled {
+            // The contract compares the same bid sum, which is emitted as sold bidding tokens.
+            if settlement.sold_bidding_tokens < self.min_funding_threshold {
+                AuctionStatus::FundingThresholdNotReached
+            } else if settlement.is_atomically_closed {
+                AuctionStatus::AtomicallyClosed
+            } else {
+                AuctionStatus::Settled
+            }
+        } else if timestamp >= self.end_time_timestamp {
+            AuctionStatus::Ended
+        } else if self.current_bidding_amount.is_zero() {
+            // bids are accepted from the creation on, hence the auction is created until a bid
+            // is placed
+            AuctionStatus::Created
+        } else if timestamp >= self.order_cancellation_end_date {
+            AuctionStatus::OpenNoCancellation
+        } else {
+            AuctionStatus::OpenWithCancellation
+        }
+    }
+}
+
+/// Lifecycle of an auction. An auction moves through the statuses in the order of their
+/// declaration, possibly skipping some, but ends in exactly one of the last three.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuctionStatus {
+    /// Bids can be placed, but there is no open bid yet.
+    Created,
+    /// Bids can be placed and canceled.
+    OpenWithCancellation,
+    /// Bids can be placed, but not canceled anymore.
+    OpenNoCancellation,
+    /// No bids can be placed anymore, but the auction is not yet settled.
+    Ended,
+    Settled,
+    /// Settled without reaching the minimal funding, hence all bids are refunded.
+    FundingThresholdNotReached,
+    /// Settled right after the end by a last bid, see `AuctionSettlement::is_atomically_closed`.
+    AtomicallyClosed,
+}
+
+impl Default for AuctionStatus {
+    fn default() -> Self {
+        AuctionStatus::Created
+    }
+}
+
+impl AuctionStatus {
+    /// Whether no bids can be placed anymore.
+    pub fn is_closed(&self) -> bool {
+        !matches!(
+            self,
+            AuctionStatus::Created
+                | AuctionStatus::OpenWithCancellation
+                | AuctionStatus::OpenNoCancellation
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuctionSortKey {
+    InterestScore,
+    EndTimeTimestamp,
+    UsdAmountTraded,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+/// Query parameters of the auction listing. Unset parameters do not restrict the listing,
+/// which is ordered by auction id unless a sort key is given.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuctionListQuery {
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+    pub status: Option<AuctionStatus>,
+    /// Matches the auctioning as well as the bidding token.
+    pub token: Option<Address>,
+    #[serde(default)]
+    pub private_only: bool,
+    pub min_usd_amount_traded: Option<Decimal>,
+    pub sort_by: Option<AuctionSortKey>,
+    /// Defaults to descending for sort keys and ascending for the auction id.
+    pub direction: Option<SortDirection>,
+}
+
+impl AuctionListQuery {
+    pub fn matches(&self, details: &AuctionDetails) -> bool {
+        self.status.map_or(true, |status| details.status == status)
+            && self.token.map_or(true, |token| {
+                details.address_auctioning_token == token || details.address_bidding_token == token
+            })
+            && (!self.private_only || details.is_private_auction)
+            && self
+                .min_usd_amount_traded
+                .map_or(true, |min| details.exact_usd_amount_traded >= min)
+    }
+
+    /// Filters, sorts and paginates the auctions.
+    pub fn apply(&self, mut auctions: Vec<AuctionDetails>) -> Vec<AuctionDetails> {
+        auctions.retain(|details| self.matches(details));
+        auctions.sort_by_key(|details| details.auction_id);
+        let direction = match self.sort_by {
+            Some(sort_key) => {
+                // stable sort, hence ties are ordered by auction id in the same direction
+                auctions.sort_by(|a, b| match sort_key {
+                    AuctionSortKey::InterestScore => a
+                        .interest_score
+                        .partial_cmp(&b.interest_score)
+                        .unwrap_or(Ordering::Equal),
+                    AuctionSortKey::EndTimeTimestamp => {
+                        a.end_time_timestamp.cmp(&b.end_time_timestamp)
+                    }
+                    AuctionSortKey::UsdAmountTraded => {
+                        a.exact_usd_amount_traded.cmp(&b.exact_usd_amount_traded)
+                    }
+                });
+                self.direction.unwrap_or(SortDirection::Descending)
+            }
+            None => self.direction.unwrap_or(SortDirection::Ascending),
+        };
+        if direction == SortDirection::Descending {
+            auctions.reverse();
+        }
+        auctions
+            .into_iter()
+            .skip(self.offset.unwrap_or(0))
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
 }
 
 // Auction details are sortable by their interest
@@ -65,3 +277,145 @@ impl Ord for AuctionDetails {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auction(auction_id: u64, interest_score: f64) -> AuctionDetails {
+        AuctionDetails {
+            auction_id,
+            interest_score,
+            starting_timestamp: 100,
+            order_cancellation_end_date: 200,
+            end_time_timestamp: 300,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn status_follows_the_auction_timeline() {
+        let created = AuctionDetails {
+            min_funding_threshold: U256::from(10),
+            ..auction(1, 0.0)
+        };
+        assert_eq!(created.status_at(150), AuctionStatus::Created);
+        assert_eq!(created.status_at(250), AuctionStatus::Created);
+        assert_eq!(created.status_at(350), AuctionStatus::Ended);
+        let details = AuctionDetails {
+            current_bidding_amount: U256::from(1),
+            ..created
+        };
+        assert_eq!(details.status_at(150), AuctionStatus::OpenWithCancellation);
+        assert_eq!(details.status_at(250), AuctionStatus::OpenNoCancellation);
+        assert_eq!(details.status_at(350), AuctionStatus::Ended);
+        assert!(!details.status_at(250).is_closed());
+        assert!(details.status_at(350).is_closed());
+        let settled = |sold_bidding_tokens: u64, is_atomically_closed| AuctionDetails {
+            settlement: AuctionSettlement {
+                is_settled: true,
+                sold_bidding_tokens: U256::from(sold_bidding_tokens),
+                is_atomically_closed,
+                ..Default::default()
+            },
+            ..details.clone()
+        };
+        assert_eq!(settled(10, false).status_at(350), AuctionStatus::Settled);
+        assert_eq!(
+            settled(10, true).status_at(350),
+            AuctionStatus::AtomicallyClosed
+        );
+        assert_eq!(
+            settled(9, true).status_at(350),
+            AuctionStatus::FundingThresholdNotReached
+        );
+    }
+
+    #[test]
+    fn sorts_and_paginates_auctions() {
+        let auctions = vec![auction(3, 1.0), auction(1, 3.0), auction(2, 2.0)];
+        let ids = |auctions: Vec<AuctionDetails>| -> Vec<u64> {
+            auctions.iter().map(|details| details.auction_id).collect()
+        };
+        assert_eq!(
+            ids(AuctionListQuery::default().apply(auctions.clone())),
+            vec![1, 2, 3]
+        );
+        let query = AuctionListQuery {
+            sort_by: Some(AuctionSortKey::InterestScore),
+            offset: Some(1),
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(ids(query.apply(auctions.clone())), vec![2]);
+        let query = AuctionListQuery {
+            sort_by: Some(AuctionSortKey::InterestScore),
+            direction: Some(SortDirection::Ascending),
+            ..Default::default()
+        };
+        assert_eq!(ids(query.apply(auctions)), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn filters_auctions() {
+        let token = Address::from_low_u64_be(7);
+        let auctions = vec![
+            AuctionDetails {
+                address_bidding_token: token,
+                ..auction(1, 0.0)
+            },
+            AuctionDetails {
+                is_private_auction: true,
+                exact_usd_amount_traded: "100".parse().unwrap(),
+                ..auction(2, 0.0)
+            },
+            AuctionDetails {
+                end_time_timestamp: 1000,
+                current_bidding_amount: U256::from(1),
+                ..auction(3, 0.0)
+            },
+        ];
+        let auctions: Vec<AuctionDetails> = auctions
+            .into_iter()
+            .map(|details| AuctionDetails {
+                status: details.status_at(500),
+                ..details
+            })
+            .collect();
+        let ids = |query: AuctionListQuery| -> Vec<u64> {
+            query
+                .apply(auctions.clone())
+                .iter()
+                .map(|details| details.auction_id)
+                .collect()
+        };
+        assert_eq!(
+            ids(AuctionListQuery {
+                token: Some(token),
+                ..Default::default()
+            }),
+            vec![1]
+        );
+        assert_eq!(
+            ids(AuctionListQuery {
+                private_only: true,
+                ..Default::default()
+            }),
+            vec![2]
+        );
+        assert_eq!(
+            ids(AuctionListQuery {
+                min_usd_amount_traded: Some("50".parse().unwrap()),
+                ..Default::default()
+            }),
+            vec![2]
+        );
+        assert_eq!(
+            ids(AuctionListQuery {
+                status: Some(AuctionStatus::OpenNoCancellation),
+                ..Default::default()
+            }),
+            vec![3]
+        );
+    }
+}
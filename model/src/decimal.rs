@@ -0,0 +1,298 @@
+use anyhow::{anyhow, Result};
+use primitive_types::{U256, U512};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// Number of fractional digits kept, if a division is not exactly representable.
+pub const DIVISION_PRECISION: u32 = 36;
+/// Largest number of fractional digits a decimal can have.
+pub const MAX_SCALE: u32 = 60;
+
+/// Non-negative fixed-point number with the value `mantissa / 10^scale`.
+///
+/// In contrast to f64, token amounts and prices of tokens with many decimals are
+/// represented without any precision loss. Decimals are serialized as exact decimal
+/// strings, e.g. "1234.5678".
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Decimal {
+    mantissa: U256,
+    scale: u32,
+}
+
+impl Decimal {
+    /// Creates the decimal `mantissa / 10^scale`, e.g. a token amount with
+    /// `scale` decimals. Digits beyond MAX_SCALE are truncated.
+    pub fn new(mantissa: U256, scale: u32) -> Self {
+        Self::from_u512(U512::from(mantissa), scale)
+            .expect("decimal with a U256 mantissa always fits")
+    }
+
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.is_zero()
+    }
+
+    /// Exact value of `numerator / denominator`, if it has at most DIVISION_PRECISION
+    /// fractional digits. Otherwise, the value is rounded down to DIVISION_PRECISION
+    /// fractional digits. Returns None for a zero denominator or if the result does not
+    /// fit.
+    pub fn from_ratio(numerator: U512, denominator: U512) -> Option<Self> {
+        if denominator.is_zero() {
+            return None;
+        }
+        let mut scale = DIVISION_PRECISION;
+        loop {
+            if let Some(scaled_numerator) = numerator.checked_mul(U512::exp10(scale as usize)) {
+                return Self::from_u512(scaled_numerator / denominator, scale);
+            }
+            // the remaining digits can not be represented anyways
+            scale = scale.checked_sub(1)?;
+        }
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        let scale = self.scale.max(other.scale);
+        Self::from_u512(
+            self.mantissa_with_scale(scale)
+                .checked_add(other.mantissa_with_scale(scale))?,
+            scale,
+        )
+    }
+
+    /// Returns None, if the result would be negative.
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        let scale = self.scale.max(other.scale);
+        Self::from_u512(
+            self.mantissa_with_scale(scale)
+                .checked_sub(other.mantissa_with_scale(scale))?,
+            scale,
+        )
+    }
+
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Self::from_u512(
+            self.mantissa.full_mul(other.mantissa),
+            self.scale + other.scale,
+        )
+    }
+
+    /// Division, with the same rounding as `Decimal::from_ratio`.
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        // (a / 10^sa) / (b / 10^sb) = (a * 10^sb) / (b * 10^sa)
+        Self::from_ratio(
+            U512::from(self.mantissa).checked_mul(U512::exp10(other.scale as usize))?,
+            U512::from(other.mantissa).checked_mul(U512::exp10(self.scale as usize))?,
+        )
+    }
+
+    pub fn to_f64_lossy(&self) -> f64 {
+        self.mantissa.to_f64_lossy() / 10_f64.powi(self.scale as i32)
+    }
+
+    /// Drops trailing zeros and the digits that do not fit into MAX_SCALE or the U256
+    /// mantissa.
+    fn from_u512(mut mantissa: U512, mut scale: u32) -> Option<Self> {
+        let ten = U512::from(10);
+        let max_mantissa = U512::from(U256::max_value());
+        while scale > 0
+            && (scale > MAX_SCALE || mantissa > max_mantissa || (mantissa % ten).is_zero())
+        {
+            mantissa /= ten;
+            scale -= 1;
+        }
+        Some(Decimal {
+            mantissa: U256::try_from(mantissa).ok()?,
+            scale,
+        })
+    }
+
+    /// Mantissa of the value with the given number of fractional digits.
+    fn mantissa_with_scale(&self, scale: u32) -> U512 {
+        U512::from(self.mantissa) * U512::exp10((scale - self.scale) as usize)
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // scales are bound by MAX_SCALE, hence the scaled mantissas fit into U512
+        let scale = self.scale.max(other.scale);
+        self.mantissa_with_scale(scale)
+            .cmp(&other.mantissa_with_scale(scale))
+    }
+}
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = self.mantissa.to_string();
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return write!(f, "{}", digits);
+        }
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (integer_part, fractional_part) = digits.split_at(digits.len() - scale);
+        write!(f, "{}.{}", integer_part, fractional_part)
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let (integer_part, fractional_part) = match s.find('.') {
+            Some(position) => (&s[..position], &s[position + 1..]),
+            None => (s, ""),
+        };
+        if integer_part.is_empty()
+            || !integer_part
+                .chars()
+                .chain(fractional_part.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            return Err(anyhow!("invalid decimal {:?}", s));
+        }
+        let mantissa = U256::from_dec_str(&format!("{}{}", integer_part, fractional_part))
+            .map_err(|err| anyhow!("invalid decimal {:?}: {:?}", s, err))?;
+        Ok(Decimal::new(mantissa, fractional_part.len() as u32))
+    }
+}
+
+impl Serialize for Decimal {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        FromStr::from_str(&s).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_and_from_str() {
+        let decimal = Decimal::new(U256::from(1_234_500), 4);
+        assert_eq!(decimal.to_string(), "123.45");
+        assert_eq!(Decimal::from_str("123.45").unwrap(), decimal);
+        assert_eq!(Decimal::new(U256::from(5), 3).to_string(), "0.005");
+        assert_eq!(Decimal::new(U256::from(500), 2).to_string(), "5");
+        assert_eq!(Decimal::zero().to_string(), "0");
+        assert_eq!(
+            Decimal::from_str("0.000000000000000001").unwrap(),
+            Decimal::new(U256::one(), 18)
+        );
+        assert!(Decimal::from_str("").is_err());
+        assert!(Decimal::from_str(".5").is_err());
+        assert!(Decimal::from_str("-1").is_err());
+        assert!(Decimal::from_str("1e5").is_err());
+    }
+
+    #[test]
+    fn serialization() {
+        let decimal = Decimal::new(U256::from(15), 1);
+        let serialized = serde_json::to_string(&decimal).unwrap();
+        assert_eq!(serialized, "\"1.5\"");
+        let deserialized: Decimal = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, decimal);
+    }
+
+    #[test]
+    fn compares_values_of_different_scales() {
+        assert_eq!(
+            Decimal::new(U256::from(10), 1),
+            Decimal::new(U256::from(1000), 3)
+        );
+        assert!(Decimal::new(U256::from(11), 1) > Decimal::new(U256::from(1000), 3));
+        assert!(Decimal::new(U256::from(1), 18) < Decimal::new(U256::from(1), 17));
+    }
+
+    #[test]
+    fn checked_sub() {
+        assert_eq!(
+            Decimal::from_str("1.5")
+                .unwrap()
+                .checked_sub(&Decimal::from_str("0.25").unwrap()),
+            Some(Decimal::from_str("1.25").unwrap())
+        );
+        assert_eq!(
+            Decimal::from_str("1")
+                .unwrap()
+                .checked_sub(&Decimal::from_str("1.0").unwrap()),
+            Some(Decimal::zero())
+        );
+        assert_eq!(
+            Decimal::from_str("0.25")
+                .unwrap()
+                .checked_sub(&Decimal::from_str("1.5").unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn from_ratio() {
+        assert_eq!(
+            Decimal::from_ratio(U512::from(1), U512::from(8)).unwrap(),
+            Decimal::from_str("0.125").unwrap()
+        );
+        assert_eq!(
+            Decimal::from_ratio(U512::from(10), U512::from(11))
+                .unwrap()
+                .to_string(),
+            "0.90909090909090909090909090909090909"
+        );
+        assert_eq!(Decimal::from_ratio(U512::from(1), U512::zero()), None);
+    }
+
+    #[test]
+    fn keeps_precision_of_18_decimal_amounts() {
+        let amount = Decimal::new(
+            U256::from_dec_str("123456789123456789123456789").unwrap(),
+            18,
+        );
+        assert_eq!(amount.to_string(), "123456789.123456789123456789");
+        let doubled = amount.checked_mul(&Decimal::new(U256::from(2), 0)).unwrap();
+        assert_eq!(doubled.to_string(), "246913578.246913578246913578");
+        assert_eq!(amount.checked_add(&amount).unwrap(), doubled);
+        assert_eq!(
+            amount
+                .checked_add(&Decimal::from_str("0.5").unwrap())
+                .unwrap()
+                .to_string(),
+            "123456789.623456789123456789"
+        );
+        assert_eq!(
+            doubled
+                .checked_div(&Decimal::new(U256::from(2), 0))
+                .unwrap(),
+            amount
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use super::decimal::Decimal;
+use super::order::PricePoint;
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+
+/// Number of price buckets, if none is requested.
+pub const DEFAULT_NUMBER_OF_BUCKETS: usize = 50;
+pub const MAX_NUMBER_OF_BUCKETS: usize = 500;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepthChartQuery {
+    pub buckets: Option<usize>,
+}
+
+impl DepthChartQuery {
+    pub fn number_of_buckets(&self) -> usize {
+        self.buckets
+            .unwrap_or(DEFAULT_NUMBER_OF_BUCKETS)
+            .max(1)
+            .min(MAX_NUMBER_OF_BUCKETS)
+    }
+}
+
+/// Volume of all bids with at least the given price, in bidding tokens.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepthPoint {
+    pub price: Decimal,
+    pub cumulative_volume: Decimal,
+}
+
+/// Cumulative bid volume of an auction, with prices in bidding tokens per auctioning token.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepthChart {
+    /// One point per price bucket at its lowest price, from the highest to the lowest price.
+    pub bids: Vec<DepthPoint>,
+    /// Current clearing price and the volume of the bids considered for the clearing.
+    pub clearing_point: DepthPoint,
+    /// Volume the bids need to reach for the auction to be settled, in bidding tokens.
+    pub min_funding_threshold: Decimal,
+}
+
+impl DepthChart {
+    /// Splits the price range of the bids, sorted from the highest to the lowest price, into
+    /// buckets of equal size.
+    pub fn new(
+        bids: &[PricePoint],
+        number_of_buckets: usize,
+        clearing_point: DepthPoint,
+        min_funding_threshold: Decimal,
+    ) -> Self {
+        DepthChart {
+            bids: cumulate_bids(bids, number_of_buckets),
+            clearing_point,
+            min_funding_threshold,
+        }
+    }
+}
+
+fn cumulate_bids(bids: &[PricePoint], number_of_buckets: usize) -> Vec<DepthPoint> {
+    let (highest_price, lowest_price) = match (bids.first(), bids.last()) {
+        (Some(highest), Some(lowest)) => (highest.exact_price, lowest.exact_price),
+        _ => return Vec::new(),
+    };
+    let bucket_size = highest_price
+        .checked_sub(&lowest_price)
+        .and_then(|price_range| {
+            price_range.checked_div(&Decimal::new(U256::from(number_of_buckets), 0))
+        })
+        .unwrap_or_default();
+    let mut points = Vec::new();
+    let mut remaining_bids = bids.iter().peekable();
+    let mut cumulative_volume = Decimal::zero();
+    let mut bucket_price = highest_price;
+    for bucket in 1..=number_of_buckets {
+        bucket_price = match bucket_price.checked_sub(&bucket_size) {
+            // the bucket size is rounded down, hence the last bucket ends at the lowest price
+            Some(price) if bucket < number_of_buckets && price > lowest_price => price,
+            _ => lowest_price,
+        };
+        while let Some(bid) = remaining_bids.next_if(|bid| bid.exact_price >= bucket_price) {
+            cumulative_volume = cumulative_volume
+                .checked_add(&bid.exact_volume)
+                .expect("bid volumes should not overflow");
+        }
+        points.push(DepthPoint {
+            price: bucket_price,
+            cumulative_volume,
+        });
+        if bucket_price == lowest_price {
+            break;
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn decimal(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn bid(price: &str, volume: &str) -> PricePoint {
+        PricePoint {
+            exact_price: decimal(price),
+            exact_volume: decimal(volume),
+            ..Default::default()
+        }
+    }
+
+    fn point(price: &str, cumulative_volume: &str) -> DepthPoint {
+        DepthPoint {
+            price: decimal(price),
+            cumulative_volume: decimal(cumulative_volume),
+        }
+    }
+
+    #[test]
+    fn cumulates_bids_per_price_bucket() {
+        let bids = vec![
+            bid("3", "1"),
+            bid("2.9", "2"),
+            bid("2", "4"),
+            bid("1.5", "8"),
+            bid("1", "16"),
+        ];
+        assert_eq!(
+            cumulate_bids(&bids, 4),
+            vec![
+                point("2.5", "3"),
+                point("2", "7"),
+                point("1.5", "15"),
+                point("1", "31"),
+            ]
+        );
+        assert_eq!(cumulate_bids(&bids, 1), vec![point("1", "31")]);
+        assert_eq!(cumulate_bids(&[], 4), Vec::new());
+    }
+
+    #[test]
+    fn uses_one_bucket_for_a_single_price() {
+        let bids = vec![bid("2", "1"), bid("2", "2")];
+        assert_eq!(cumulate_bids(&bids, 10), vec![point("2", "3")]);
+    }
+
+    #[test]
+    fn limits_number_of_buckets() {
+        let query = |buckets| DepthChartQuery { buckets };
+        assert_eq!(query(None).number_of_buckets(), DEFAULT_NUMBER_OF_BUCKETS);
+        assert_eq!(query(Some(0)).number_of_buckets(), 1);
+        assert_eq!(
+            query(Some(10_000)).number_of_buckets(),
+            MAX_NUMBER_OF_BUCKETS
+        );
+    }
+}
@@ -1,6 +1,9 @@
 pub mod auction_details;
+pub mod decimal;
+pub mod depth_chart;
 pub mod order;
 pub mod signature_object;
+pub mod stats;
 pub mod user;
 
 use ethabi::{encode, Token};
@@ -1,5 +1,7 @@
+use crate::decimal::Decimal;
+use anyhow::{anyhow, Result};
 use lazy_static::lazy_static;
-use primitive_types::U256;
+use primitive_types::{U256, U512};
 use serde::Serialize;
 use serde::Serializer;
 use serde::{de, Deserialize, Deserializer};
@@ -27,26 +29,68 @@ pub struct OrderbookDisplay {
     pub bids: Vec<PricePoint>,
 }
 #[derive(Default, Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PricePoint {
+    /// Lossy approximation of exact_price, only kept for backward compatibility.
     pub price: f64,
+    /// Lossy approximation of exact_volume, only kept for backward compatibility.
     pub volume: f64,
+    #[serde(default)]
+    pub exact_price: Decimal,
+    #[serde(default)]
+    pub exact_volume: Decimal,
 }
 impl PricePoint {
     pub fn invert_price(&self) -> Self {
         PricePoint {
             price: 1_f64 / self.price,
             volume: self.volume,
+            exact_price: Decimal::new(U256::one(), 0)
+                .checked_div(&self.exact_price)
+                .unwrap_or_default(),
+            exact_volume: self.exact_volume,
         }
     }
 }
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FillStatus {
+    Filled,
+    PartiallyFilled,
+    Unfilled,
+}
+
+/// Amounts an order would receive, if it was claimed at a given clearing order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderSimulation {
+    pub order: Order,
+    pub fill_status: FillStatus,
+    pub auctioning_token_amount: U256,
+    pub bidding_token_refund: U256,
+}
+
+/// Clearing of an auction, if a hypothetical bid was placed in it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BidSimulation {
+    pub clearing_order: Order,
+    pub volume_clearing_order: U256,
+    pub bidding_amount: U256,
+    pub is_in_the_money: bool,
+    /// Whether the bid is inserted. A bid equal to a placed order is ignored by the contract
+    /// without reverting, in which case the clearing does not change.
+    pub is_inserted: bool,
+    pub order_simulation: OrderSimulation,
+}
+
 lazy_static! {
     pub static ref TEN: U256 = U256::from_dec_str("10").unwrap();
     pub static ref EIGHTEEN: U256 = U256::from_dec_str("18").unwrap();
 }
 impl PartialEq for PricePoint {
     fn eq(&self, other: &Self) -> bool {
-        float_cmp::approx_eq!(f64, self.volume, other.volume, ulps = 2)
-            && float_cmp::approx_eq!(f64, self.price, other.price, ulps = 2)
+        self.exact_volume == other.exact_volume && self.exact_price == other.exact_price
     }
 }
 impl Eq for PricePoint {}
@@ -59,58 +103,120 @@ impl PartialOrd for PricePoint {
 
 impl Ord for PricePoint {
     fn cmp(&self, other: &Self) -> Ordering {
-        if self.price > other.price {
+        if self.exact_price > other.exact_price {
             return Ordering::Less;
         }
-        if self.price < other.price {
+        if self.exact_price < other.exact_price {
             return Ordering::Greater;
         }
-        self.volume.partial_cmp(&other.volume).unwrap()
+        self.exact_volume.cmp(&other.exact_volume)
     }
 }
 
 impl Order {
+    /// Price of the order in buy tokens per sell token, or `None` if it can not be
+    /// represented, i.e. the amounts overflow when scaled by the decimals of the tokens or
+    /// the buy amount is zero.
     pub fn convert_to_price_point(
         &self,
         decimals_buy_token: U256,
         decimals_sell_token: U256,
-    ) -> PricePoint {
-        let mut price_denominator = self
-            .buy_amount
-            .checked_mul(TEN.pow(decimals_sell_token))
-            .expect("buy_amount should not overflow")
-            .to_f64_lossy();
-        // avoid special case where we would divide by zero
-        if price_denominator == 0_f64 {
-            price_denominator = 1_f64;
-        }
-        let price_numerator = self
-            .sell_amount
-            .checked_mul(TEN.pow(decimals_buy_token))
-            .expect("sell_amount should not overflow")
-            .to_f64_lossy();
-        let volume_numerator = self.sell_amount.to_f64_lossy();
-        let volume_denominator = (TEN.pow(decimals_sell_token)).to_f64_lossy();
-        PricePoint {
-            price: price_numerator / price_denominator,
-            volume: volume_numerator / volume_denominator,
+    ) -> Option<PricePoint> {
+        let scale_buy_token = TEN.checked_pow(decimals_buy_token)?;
+        let scale_sell_token = TEN.checked_pow(decimals_sell_token)?;
+        let price_numerator = self.sell_amount.checked_mul(scale_buy_token)?;
+        let price_denominator = self.buy_amount.checked_mul(scale_sell_token)?;
+        let exact_price =
+            Decimal::from_ratio(U512::from(price_numerator), U512::from(price_denominator))?;
+        Some(PricePoint {
+            price: price_numerator.to_f64_lossy() / price_denominator.to_f64_lossy(),
+            volume: self.sell_amount.to_f64_lossy() / scale_sell_token.to_f64_lossy(),
+            exact_price,
+            exact_volume: Decimal::new(self.sell_amount, decimals_sell_token.as_u32()),
+        })
+    }
+}
+
+impl Order {
+    /// user_id + buy_amount + sell_amount, as the order is encoded on-chain
+    pub fn convert_to_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        let mut interim_bytes = [0u8; 32];
+        bytes[..8].copy_from_slice(&self.user_id.to_be_bytes());
+        self.buy_amount.to_big_endian(&mut interim_bytes);
+        bytes[8..20].copy_from_slice(&interim_bytes[20..32]);
+        self.sell_amount.to_big_endian(&mut interim_bytes);
+        bytes[20..32].copy_from_slice(&interim_bytes[20..32]);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8; 32]) -> Self {
+        Order {
+            sell_amount: U256::from_big_endian(&bytes[20..32]),
+            buy_amount: U256::from_big_endian(&bytes[8..20]),
+            user_id: u64::from_be_bytes(bytes[..8].try_into().expect("conversion not possible")),
         }
     }
 }
 
+impl Order {
+    /// Settlement maths of `claimFromParticipantOrder` in the smart contract, without fees.
+    pub fn simulate_claim(
+        &self,
+        clearing_order: &Order,
+        volume_clearing_order: U256,
+        min_funding_threshold_reached: bool,
+    ) -> Result<OrderSimulation> {
+        let price_numerator = clearing_order.buy_amount;
+        let price_denominator = clearing_order.sell_amount;
+        let (auctioning_token_amount, bidding_token_refund) = if !min_funding_threshold_reached {
+            (U256::zero(), self.sell_amount)
+        } else if self == clearing_order {
+            (
+                volume_clearing_order
+                    .checked_mul(price_numerator)
+                    .ok_or_else(|| anyhow!("error in simulate_claim"))?
+                    .checked_div(price_denominator)
+                    .ok_or_else(|| anyhow!("error in simulate_claim"))?,
+                self.sell_amount
+                    .checked_sub(volume_clearing_order)
+                    .ok_or_else(|| anyhow!("error in simulate_claim"))?,
+            )
+        } else if self < clearing_order {
+            (
+                self.sell_amount
+                    .checked_mul(price_numerator)
+                    .ok_or_else(|| anyhow!("error in simulate_claim"))?
+                    .checked_div(price_denominator)
+                    .ok_or_else(|| anyhow!("error in simulate_claim"))?,
+                U256::zero(),
+            )
+        } else {
+            (U256::zero(), self.sell_amount)
+        };
+        let fill_status = if bidding_token_refund.is_zero() {
+            FillStatus::Filled
+        } else if bidding_token_refund == self.sell_amount {
+            FillStatus::Unfilled
+        } else {
+            FillStatus::PartiallyFilled
+        };
+        Ok(OrderSimulation {
+            order: *self,
+            fill_status,
+            auctioning_token_amount,
+            bidding_token_refund,
+        })
+    }
+}
+
 impl FromStr for Order {
     type Err = hex::FromHexError;
     fn from_str(s: &str) -> Result<Order, hex::FromHexError> {
         let s_without_prefix = s.strip_prefix("0x").unwrap_or(s);
         let mut bytes = [0u8; 32];
         hex::decode_to_slice(s_without_prefix, &mut bytes)?;
-        // let sell_amount_bytes: [u8; 32] = bytes[20..32].try_into().expect("slice with incorrect length");
-        // let buy_amount_bytes: [u8;32] = .try_into().expect("slice with incorrect length");
-        Ok(Order {
-            sell_amount: U256::from_big_endian(&bytes[20..32]),
-            buy_amount: U256::from_big_endian(&bytes[8..20]),
-            user_id: u64::from_be_bytes(bytes[..8].try_into().expect("conversion not possible")),
-        })
+        Ok(Order::from_bytes(&bytes))
     }
 }
 
@@ -200,6 +306,23 @@ mod tests {
         assert_eq!(deserialized, order);
     }
 
+    #[test]
+    fn convert_to_bytes_and_from_bytes() {
+        let order = Order {
+            sell_amount: U256::from_dec_str("1230").unwrap(),
+            buy_amount: U256::from_dec_str("123").unwrap(),
+            user_id: 10_u64,
+        };
+        let bytes = order.convert_to_bytes();
+        assert_eq!(
+            bytes,
+            Order::from_str("0x000000000000000a00000000000000000000007b0000000000000000000004ce")
+                .unwrap()
+                .convert_to_bytes()
+        );
+        assert_eq!(Order::from_bytes(&bytes), order);
+    }
+
     #[test]
     fn ordering_of_orders() {
         let normal_order = Order {
@@ -217,6 +340,53 @@ mod tests {
         assert_eq!(higher_priced_order.cmp(&normal_order), Ordering::Greater);
     }
 
+    #[test]
+    fn simulate_claim_of_orders() {
+        let clearing_order = Order {
+            sell_amount: U256::from_dec_str("20").unwrap(),
+            buy_amount: U256::from_dec_str("10").unwrap(),
+            user_id: 3_u64,
+        };
+        let better_order = Order {
+            sell_amount: U256::from_dec_str("30").unwrap(),
+            buy_amount: U256::from_dec_str("10").unwrap(),
+            user_id: 1_u64,
+        };
+        let worse_order = Order {
+            sell_amount: U256::from_dec_str("10").unwrap(),
+            buy_amount: U256::from_dec_str("10").unwrap(),
+            user_id: 2_u64,
+        };
+        let volume_clearing_order = U256::from_dec_str("8").unwrap();
+
+        let simulation = better_order
+            .simulate_claim(&clearing_order, volume_clearing_order, true)
+            .unwrap();
+        assert_eq!(simulation.fill_status, FillStatus::Filled);
+        assert_eq!(simulation.auctioning_token_amount, U256::from(15));
+        assert_eq!(simulation.bidding_token_refund, U256::zero());
+
+        let simulation = clearing_order
+            .simulate_claim(&clearing_order, volume_clearing_order, true)
+            .unwrap();
+        assert_eq!(simulation.fill_status, FillStatus::PartiallyFilled);
+        assert_eq!(simulation.auctioning_token_amount, U256::from(4));
+        assert_eq!(simulation.bidding_token_refund, U256::from(12));
+
+        let simulation = worse_order
+            .simulate_claim(&clearing_order, volume_clearing_order, true)
+            .unwrap();
+        assert_eq!(simulation.fill_status, FillStatus::Unfilled);
+        assert_eq!(simulation.auctioning_token_amount, U256::zero());
+        assert_eq!(simulation.bidding_token_refund, U256::from(10));
+
+        let simulation = better_order
+            .simulate_claim(&clearing_order, volume_clearing_order, false)
+            .unwrap();
+        assert_eq!(simulation.fill_status, FillStatus::Unfilled);
+        assert_eq!(simulation.bidding_token_refund, U256::from(30));
+    }
+
     #[test]
     fn convert_to_price_point_with_18_digits() {
         let normal_order = Order {
@@ -227,9 +397,13 @@ mod tests {
         let expected_price_point = PricePoint {
             price: 10_f64 / 11_f64,
             volume: 100.0_f64,
+            exact_price: Decimal::from_str("0.90909090909090909090909090909090909").unwrap(),
+            exact_volume: Decimal::from_str("100").unwrap(),
         };
         assert_eq!(
-            normal_order.convert_to_price_point(*EIGHTEEN, *EIGHTEEN),
+            normal_order
+                .convert_to_price_point(*EIGHTEEN, *EIGHTEEN)
+                .unwrap(),
             expected_price_point
         );
     }
@@ -243,10 +417,35 @@ mod tests {
         let expected_price_point = PricePoint {
             price: 10_f64 / (11_f64 * 10_f64.powi(12)),
             volume: 100.0_f64,
+            exact_price: Decimal::from_str("0.00000000000090909090909090909090909").unwrap(),
+            exact_volume: Decimal::from_str("100").unwrap(),
         };
         assert_eq!(
-            normal_order.convert_to_price_point(U256::from("6"), *EIGHTEEN),
+            normal_order
+                .convert_to_price_point(U256::from("6"), *EIGHTEEN)
+                .unwrap(),
             expected_price_point
         );
     }
+    #[test]
+    fn convert_to_price_point_without_representable_price() {
+        let order = Order {
+            sell_amount: U256::from(100),
+            buy_amount: U256::from(110),
+            user_id: 10_u64,
+        };
+        assert!(order
+            .convert_to_price_point(U256::from(255), *EIGHTEEN)
+            .is_none());
+        assert!(order
+            .convert_to_price_point(*EIGHTEEN, U256::from(76))
+            .is_none());
+        let free_order = Order {
+            buy_amount: U256::zero(),
+            ..order
+        };
+        assert!(free_order
+            .convert_to_price_point(*EIGHTEEN, *EIGHTEEN)
+            .is_none());
+    }
 }
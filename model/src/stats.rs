@@ -0,0 +1,45 @@
+use super::decimal::Decimal;
+use ethcontract::Address;
+use serde::{Deserialize, Serialize};
+
+/// Restricts the statistics to the auctions started within the time range.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsFilter {
+    pub from_timestamp: Option<u64>,
+    pub to_timestamp: Option<u64>,
+}
+
+impl StatsFilter {
+    pub fn contains(&self, timestamp: u64) -> bool {
+        self.from_timestamp.map_or(true, |from| from <= timestamp)
+            && self.to_timestamp.map_or(true, |to| timestamp <= to)
+    }
+}
+
+/// Aggregated statistics of all auctions of a chain.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformStats {
+    pub total_auctions: u64,
+    pub active_auctions: u64,
+    pub closed_auctions: u64,
+    pub private_auctions: u64,
+    pub usd_amount_traded: Decimal,
+    pub unique_bidders: u64,
+    /// Average number of open and claimed bids per auction. Cancelled bids are not counted,
+    /// while claiming keeps the bids counted, hence the value does not change after the
+    /// settlement.
+    pub bids_per_auction: f64,
+    /// Bidding tokens with the largest USD amount traded.
+    pub top_bidding_tokens: Vec<TokenStats>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenStats {
+    pub address: Address,
+    pub symbol: String,
+    pub auctions: u64,
+    pub usd_amount_traded: Decimal,
+}
@@ -1,6 +1,8 @@
 use ethcontract::H160;
+use serde::{Deserialize, Serialize};
 
-#[derive(Eq, PartialEq, Clone, Debug, Copy, Default)]
+#[derive(Eq, PartialEq, Clone, Debug, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct User {
     pub address: H160,
     pub user_id: u64,
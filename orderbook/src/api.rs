@@ -2,47 +2,204 @@ mod filter;
 mod handler;
 
 use crate::database::Database;
-use crate::health::HttpHealthEndpoint;
 use crate::orderbook::Orderbook;
+use crate::ChainOrderbook;
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
 use std::sync::Arc;
+use subtle::ConstantTimeEq;
 use warp::Filter;
 
+/// Token, which has to be sent as `Authorization: Bearer {token}` header to the admin routes.
+/// It is not printed, such that it does not end up in the logs.
+#[derive(Clone)]
+pub struct AdminToken(pub String);
+
+impl AdminToken {
+    /// Whether the `Authorization` header carries the token. The header is compared in
+    /// constant time, such that the token can not be guessed from the response times.
+    pub fn authorizes(&self, authorization: Option<&str>) -> bool {
+        let expected = format!("Bearer {}", self.0);
+        authorization.map_or(false, |authorization| {
+            bool::from(authorization.as_bytes().ct_eq(expected.as_bytes()))
+        })
+    }
+}
+
+impl fmt::Debug for AdminToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AdminToken(..)")
+    }
+}
+
+impl FromStr for AdminToken {
+    type Err = Infallible;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(AdminToken(s.to_string()))
+    }
+}
+
+/// Serves the routes of each chain under `/api/v1/{chain_id}/`. The admin routes are only
+/// served with an admin token.
 pub fn handle_all_routes(
+    chains: Vec<ChainOrderbook>,
+    database: Database,
+    admin_token: Option<AdminToken>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let health_filter =
+        filter::health_filter_readiness(chains.iter().map(|chain| chain.health.clone()).collect());
+    let metrics_filter = filter::get_metrics(
+        chains
+            .iter()
+            .map(|chain| (chain.chain_id, chain.metrics.clone()))
+            .collect(),
+    );
+    let chain_routes = chains
+        .into_iter()
+        .map(|chain| {
+            warp::path(chain.chain_id.to_string())
+                .and(handle_chain_routes(
+                    chain.orderbook,
+                    database.clone(),
+                    chain.chain_id,
+                    admin_token.clone(),
+                ))
+                .boxed()
+        })
+        .reduce(|routes, other_routes| routes.or(other_routes).unify().boxed())
+        .expect("at least one chain has to be served");
+    let api_routes = warp::path!("api" / "v1" / ..).and(chain_routes);
+    let health_routes = warp::path!("health" / ..).and(health_filter);
+    api_routes.or(health_routes).or(metrics_filter)
+}
+
+fn handle_chain_routes(
     orderbook: Arc<Orderbook>,
     database: Database,
-    health: Arc<HttpHealthEndpoint>,
+    chain_id: u32,
+    admin_token: Option<AdminToken>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
-    let get_signature = filter::get_signature(database.clone());
+    let get_signature = filter::get_signature(database.clone(), chain_id);
+    let get_clearing_price_history = filter::get_clearing_price_history(database.clone(), chain_id);
     let provide_signatures_object = filter::provide_signatures_object(orderbook.clone(), database);
     let get_previous_order = filter::get_previous_order(orderbook.clone());
     let get_order_book_display_data = filter::get_order_book_display_data(orderbook.clone());
+    let get_depth_chart = filter::get_depth_chart(orderbook.clone());
     let get_user_orders = filter::get_user_orders(orderbook.clone());
-    let health_filter = filter::health_filter_readiness(health);
+    let simulate_user_orders = filter::simulate_user_orders(orderbook.clone());
+    let simulate_bid = filter::simulate_bid(orderbook.clone());
+    let get_user = filter::get_user(orderbook.clone());
+    let get_user_id = filter::get_user_id(orderbook.clone());
     let get_user_orders_without_claimed =
         filter::get_user_orders_without_canceled_or_claimed(orderbook.clone());
     let get_clearing_order_and_volume = filter::get_clearing_order_and_volume(orderbook.clone());
+    let stream_auction_updates = filter::stream_auction_updates(orderbook.clone());
     let get_details_of_most_interesting_auctions =
         filter::get_details_of_most_interesting_auctions(orderbook.clone());
     let get_details_of_most_interesting_closed_auctions =
         filter::get_details_of_most_interesting_closed_auctions(orderbook.clone());
     let get_all_auction_with_details = filter::get_all_auction_with_details(orderbook.clone());
     let get_auction_with_details = filter::get_auction_with_details(orderbook.clone());
+    let get_stats = filter::get_stats(orderbook.clone());
+    let search_auctions = filter::search_auctions(orderbook.clone());
+    let get_dead_letters = filter::get_dead_letters(orderbook.clone(), admin_token);
     let get_all_auction_with_details_with_user_participation =
         filter::get_all_auction_with_details_with_user_participation(orderbook);
-    let api_routes = warp::path!("api" / "v1" / ..).and(
-        get_previous_order
-            .or(get_order_book_display_data)
-            .or(get_user_orders)
-            .or(get_user_orders_without_claimed)
-            .or(get_clearing_order_and_volume)
-            .or(get_details_of_most_interesting_auctions)
-            .or(get_details_of_most_interesting_closed_auctions)
-            .or(get_all_auction_with_details)
-            .or(get_auction_with_details)
-            .or(get_all_auction_with_details_with_user_participation)
-            .or(get_signature)
-            .or(provide_signatures_object),
-    );
-    let health_routes = warp::path!("health" / ..).and(health_filter);
-    api_routes.or(health_routes)
+    get_previous_order
+        .or(get_order_book_display_data)
+        .or(get_depth_chart)
+        .or(get_user_orders)
+        .or(simulate_user_orders)
+        .or(simulate_bid)
+        .or(get_user)
+        .or(get_user_id)
+        .or(get_user_orders_without_claimed)
+        .or(get_clearing_order_and_volume)
+        .or(stream_auction_updates)
+        .or(get_details_of_most_interesting_auctions)
+        .or(get_details_of_most_interesting_closed_auctions)
+        .or(get_all_auction_with_details)
+        .or(get_auction_with_details)
+        .or(get_stats)
+        .or(search_auctions)
+        .or(get_dead_letters)
+        .or(get_all_auction_with_details_with_user_participation)
+        .or(get_signature)
+        .or(get_clearing_price_history)
+        .or(provide_signatures_object)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::health::HttpHealthEndpoint;
+    use crate::metrics::SyncMetrics;
+    use model::auction_details::AuctionDetails;
+    use warp::{http::StatusCode, test::request};
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn serves_the_orderbook_of_each_chain_under_its_chain_id() {
+        let mainnet_orderbook = Orderbook::default();
+        mainnet_orderbook
+            .set_auction_details(1, AuctionDetails::default())
+            .await
+            .unwrap();
+        let chain = |chain_id, orderbook| ChainOrderbook {
+            chain_id,
+            orderbook: Arc::new(orderbook),
+            health: Arc::new(HttpHealthEndpoint::new()),
+            metrics: Arc::new(SyncMetrics::new()),
+        };
+        let filter = handle_all_routes(
+            vec![
+                chain(1, mainnet_orderbook),
+                chain(100, Orderbook::default()),
+            ],
+            Database::new("postgresql://").unwrap(),
+            None,
+        );
+        let get_auction = |path: &'static str| request().path(path).method("GET");
+
+        let response = get_auction("/api/v1/1/get_auction_with_details/1")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = get_auction("/api/v1/100/get_auction_with_details/1")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = get_auction("/api/v1/4/get_auction_with_details/1")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = get_auction("/api/v1/get_auction_with_details/1")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn serves_the_metrics_of_all_chains() {
+        let metrics = Arc::new(SyncMetrics::new());
+        metrics.set_blocks_per_request(250);
+        let filter = handle_all_routes(
+            vec![ChainOrderbook {
+                chain_id: 100,
+                orderbook: Arc::new(Orderbook::default()),
+                health: Arc::new(HttpHealthEndpoint::new()),
+                metrics,
+            }],
+            Database::new("postgresql://").unwrap(),
+            None,
+        );
+        let response = request()
+            .path("/metrics")
+            .method("GET")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = String::from_utf8(response.body().to_vec()).unwrap();
+        assert!(body.contains("orderbook_blocks_per_request{chain_id=\"100\"} 250"));
+    }
 }
@@ -1,10 +1,17 @@
 use super::handler;
-use crate::api::handler::extract_signatures_object_from_json;
+use crate::api::handler::{
+    extract_bid_from_json, extract_signatures_object_from_json, SearchQuery,
+};
+use crate::api::AdminToken;
 use crate::database::Database;
 use crate::health::HttpHealthEndpoint;
+use crate::metrics::SyncMetrics;
 use crate::orderbook::Orderbook;
 use hex::{FromHex, FromHexError};
+use model::auction_details::AuctionListQuery;
+use model::depth_chart::DepthChartQuery;
 use model::order::Order;
+use model::stats::StatsFilter;
 use primitive_types::H160;
 use std::{str::FromStr, sync::Arc};
 use warp::Filter;
@@ -16,12 +23,37 @@ fn with_orderbook(
 }
 
 fn with_health(
-    health: Arc<HttpHealthEndpoint>,
-) -> impl Filter<Extract = (Arc<HttpHealthEndpoint>,), Error = std::convert::Infallible> + Clone {
+    health: Vec<Arc<HttpHealthEndpoint>>,
+) -> impl Filter<Extract = (Vec<Arc<HttpHealthEndpoint>>,), Error = std::convert::Infallible> + Clone
+{
     warp::any().map(move || health.clone())
 }
 
-fn with_signatures(
+fn with_metrics(
+    metrics: Vec<(u32, Arc<SyncMetrics>)>,
+) -> impl Filter<Extract = (Vec<(u32, Arc<SyncMetrics>)>,), Error = std::convert::Infallible> + Clone
+{
+    warp::any().map(move || metrics.clone())
+}
+
+fn with_chain_id(
+    chain_id: u32,
+) -> impl Filter<Extract = (u32,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || chain_id)
+}
+
+/// Admin routes are not served without an admin token, hence their requests are rejected
+/// as not found.
+fn with_admin_token(
+    admin_token: Option<AdminToken>,
+) -> impl Filter<Extract = (AdminToken,), Error = warp::Rejection> + Clone {
+    warp::any().and_then(move || {
+        let admin_token = admin_token.clone();
+        async move { admin_token.ok_or_else(warp::reject::not_found) }
+    })
+}
+
+fn with_database(
     db: Database,
 ) -> impl Filter<Extract = (Database,), Error = std::convert::Infallible> + Clone {
     warp::any().map(move || db.clone())
@@ -46,8 +78,9 @@ pub fn get_previous_order(
         .and_then(handler::get_previous_order)
 }
 
+/// Readiness of the service, which is ready once the orderbooks of all chains are synced.
 pub fn health_filter_readiness(
-    health: Arc<HttpHealthEndpoint>,
+    health: Vec<Arc<HttpHealthEndpoint>>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("readiness")
         .and(warp::get())
@@ -55,6 +88,15 @@ pub fn health_filter_readiness(
         .and_then(handler::readiness)
 }
 
+pub fn get_metrics(
+    metrics: Vec<(u32, Arc<SyncMetrics>)>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("metrics")
+        .and(warp::get())
+        .and(with_metrics(metrics))
+        .and_then(handler::get_metrics)
+}
+
 pub fn get_user_orders(
     orderbook: Arc<Orderbook>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
@@ -72,6 +114,25 @@ pub fn get_clearing_order_and_volume(
         .and_then(handler::get_clearing_order_and_volume)
 }
 
+pub fn simulate_user_orders(
+    orderbook: Arc<Orderbook>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("simulate_user_orders" / u64 / H160Wrapper)
+        .and(warp::get())
+        .and(with_orderbook(orderbook))
+        .and_then(handler::simulate_user_orders)
+}
+
+pub fn simulate_bid(
+    orderbook: Arc<Orderbook>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("simulate_bid" / u64)
+        .and(warp::post())
+        .and(with_orderbook(orderbook))
+        .and(extract_bid_from_json())
+        .and_then(handler::simulate_bid)
+}
+
 pub fn get_user_orders_without_canceled_or_claimed(
     orderbook: Arc<Orderbook>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
@@ -90,6 +151,25 @@ pub fn get_order_book_display_data(
         .and_then(handler::get_order_book_display_data)
 }
 
+pub fn get_depth_chart(
+    orderbook: Arc<Orderbook>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("get_depth_chart" / u64)
+        .and(warp::get())
+        .and(warp::query::<DepthChartQuery>())
+        .and(with_orderbook(orderbook))
+        .and_then(handler::get_depth_chart)
+}
+
+pub fn stream_auction_updates(
+    orderbook: Arc<Orderbook>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("stream_auction_updates" / u64)
+        .and(warp::get())
+        .and(with_orderbook(orderbook))
+        .and_then(handler::stream_auction_updates)
+}
+
 pub fn get_details_of_most_interesting_auctions(
     orderbook: Arc<Orderbook>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
@@ -120,10 +200,43 @@ pub fn get_all_auction_with_details(
     orderbook: Arc<Orderbook>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("get_all_auction_with_details")
+        .and(warp::query::<AuctionListQuery>())
         .and(with_orderbook(orderbook))
         .and_then(handler::get_all_auction_with_details)
 }
 
+pub fn search_auctions(
+    orderbook: Arc<Orderbook>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("search")
+        .and(warp::get())
+        .and(warp::query::<SearchQuery>())
+        .and(with_orderbook(orderbook))
+        .and_then(handler::search_auctions)
+}
+
+pub fn get_stats(
+    orderbook: Arc<Orderbook>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("stats")
+        .and(warp::get())
+        .and(warp::query::<StatsFilter>())
+        .and(with_orderbook(orderbook))
+        .and_then(handler::get_stats)
+}
+
+pub fn get_dead_letters(
+    orderbook: Arc<Orderbook>,
+    admin_token: Option<AdminToken>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "dead_letters")
+        .and(warp::get())
+        .and(with_admin_token(admin_token))
+        .and(warp::header::optional::<String>("authorization"))
+        .and(with_orderbook(orderbook))
+        .and_then(handler::get_dead_letters)
+}
+
 pub fn get_all_auction_with_details_with_user_participation(
     orderbook: Arc<Orderbook>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
@@ -133,14 +246,45 @@ pub fn get_all_auction_with_details_with_user_participation(
         .and_then(handler::get_all_auction_with_details_with_user_participation)
 }
 
+pub fn get_user(
+    orderbook: Arc<Orderbook>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("get_user" / u64)
+        .and(warp::get())
+        .and(with_orderbook(orderbook))
+        .and_then(handler::get_user)
+}
+
+pub fn get_user_id(
+    orderbook: Arc<Orderbook>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("get_user_id" / H160Wrapper)
+        .and(warp::get())
+        .and(with_orderbook(orderbook))
+        .and_then(handler::get_user_id)
+}
+
 pub fn get_signature(
     db: Database,
+    chain_id: u32,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::path!("get_signature" / u64 / H160Wrapper)
         .and(warp::get())
-        .and(with_signatures(db))
+        .and(with_database(db))
+        .and(with_chain_id(chain_id))
         .and_then(handler::get_signature)
 }
+
+pub fn get_clearing_price_history(
+    db: Database,
+    chain_id: u32,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("get_clearing_price_history" / u64)
+        .and(warp::get())
+        .and(with_database(db))
+        .and(with_chain_id(chain_id))
+        .and_then(handler::get_clearing_price_history)
+}
 pub fn provide_signatures_object(
     orderbook: Arc<Orderbook>,
     db: Database,
@@ -148,7 +292,7 @@ pub fn provide_signatures_object(
     warp::path!("provide_signature")
         .and(warp::post())
         .and(with_orderbook(orderbook))
-        .and(with_signatures(db))
+        .and(with_database(db))
         .and(extract_signatures_object_from_json())
         .and_then(handler::provide_signatures)
 }
@@ -156,12 +300,16 @@ pub fn provide_signatures_object(
 #[cfg(test)]
 pub mod test_util {
     use super::*;
-    use crate::api::handler::AuctionDetailsForUser;
+    use crate::api::handler::{AuctionDetailsForUser, BidSimulationRequest};
     use crate::database::SignatureFilter;
     use futures::TryStreamExt;
     use model::auction_details::AuctionDetails;
+    use model::decimal::Decimal;
+    use model::depth_chart::{DepthChart, DepthPoint};
+    use model::order::{BidSimulation, FillStatus, OrderSimulation};
     use model::signature_object::SignaturePackage;
     use model::signature_object::SignaturesObject;
+    use model::stats::PlatformStats;
     use model::user::User;
     use model::Signature;
     use primitive_types::U256;
@@ -198,6 +346,63 @@ pub mod test_util {
         assert_eq!(response_order, order_1);
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn get_depth_chart_() {
+        let mut orderbook = Orderbook::default();
+        let auction_id: u64 = 1;
+        let initial_order = Order {
+            sell_amount: U256::from_dec_str("4").unwrap(),
+            buy_amount: U256::from_dec_str("2").unwrap(),
+            user_id: 1_u64,
+        };
+        let order_1 = Order {
+            sell_amount: U256::from_dec_str("2").unwrap(),
+            buy_amount: U256::from_dec_str("1").unwrap(),
+            user_id: 10_u64,
+        };
+        let order_2 = Order {
+            sell_amount: U256::from_dec_str("3").unwrap(),
+            buy_amount: U256::from_dec_str("3").unwrap(),
+            user_id: 10_u64,
+        };
+        orderbook
+            .set_auction_details(auction_id, AuctionDetails::default())
+            .await
+            .unwrap();
+        orderbook
+            .update_initial_order(auction_id, initial_order)
+            .await;
+        orderbook
+            .insert_orders(auction_id, vec![order_1, order_2])
+            .await;
+        let filter = get_depth_chart(Arc::new(orderbook));
+        let response = request()
+            .path(&format!("/get_depth_chart/{:}?buckets=2", auction_id))
+            .method("GET")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let depth_chart: DepthChart = serde_json::from_slice(response.body()).unwrap();
+        let point = |price: &str, cumulative_volume: &str| DepthPoint {
+            price: price.parse().unwrap(),
+            cumulative_volume: cumulative_volume.parse().unwrap(),
+        };
+        assert_eq!(
+            depth_chart,
+            DepthChart {
+                bids: vec![point("1.5", "2"), point("1", "5")],
+                clearing_point: point("1", "4"),
+                min_funding_threshold: Decimal::zero(),
+            }
+        );
+        let response = request()
+            .path("/get_depth_chart/2")
+            .method("GET")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn get_user_orders_() {
         let orderbook = Orderbook::default();
@@ -244,6 +449,135 @@ pub mod test_util {
         assert_eq!(response_order, vec![order_1]);
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn simulate_user_orders_() {
+        let mut orderbook = Orderbook::default();
+        let auction_id: u64 = 1;
+        let order_1 = Order {
+            sell_amount: U256::from_dec_str("2").unwrap(),
+            buy_amount: U256::from_dec_str("2").unwrap(),
+            user_id: 10_u64,
+        };
+        let order_2 = Order {
+            sell_amount: U256::from_dec_str("2").unwrap(),
+            buy_amount: U256::from_dec_str("1").unwrap(),
+            user_id: 9_u64,
+        };
+        let initial_order = Order {
+            sell_amount: U256::from_dec_str("4").unwrap(),
+            buy_amount: U256::from_dec_str("2").unwrap(),
+            user_id: 1_u64,
+        };
+        let user = User {
+            address: "740a98F8f4fAe0986FB3264Fe4aaCf94ac1EE96f".parse().unwrap(),
+            user_id: 10_u64,
+        };
+        orderbook
+            .set_auction_details(auction_id, AuctionDetails::default())
+            .await
+            .unwrap();
+        orderbook
+            .update_initial_order(auction_id, initial_order)
+            .await;
+        orderbook
+            .insert_orders(auction_id, vec![order_2, order_1])
+            .await;
+        orderbook.insert_users(vec![user]).await;
+        let filter = simulate_user_orders(Arc::new(orderbook));
+        let response = request()
+            .path(&format!(
+                "/simulate_user_orders/{:}/{:}",
+                auction_id,
+                user.show_full_address()
+            ))
+            .method("GET")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response_simulations: Vec<OrderSimulation> =
+            serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(
+            response_simulations,
+            vec![OrderSimulation {
+                order: order_1,
+                fill_status: FillStatus::Filled,
+                auctioning_token_amount: U256::from(2),
+                bidding_token_refund: U256::zero(),
+            }]
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn simulate_bid_() {
+        let mut orderbook = Orderbook::default();
+        let auction_id: u64 = 1;
+        let order = Order {
+            sell_amount: U256::from_dec_str("2").unwrap(),
+            buy_amount: U256::from_dec_str("1").unwrap(),
+            user_id: 9_u64,
+        };
+        let initial_order = Order {
+            sell_amount: U256::from_dec_str("4").unwrap(),
+            buy_amount: U256::from_dec_str("2").unwrap(),
+            user_id: 1_u64,
+        };
+        orderbook
+            .set_auction_details(
+                auction_id,
+                AuctionDetails {
+                    end_time_timestamp: u64::MAX,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        orderbook
+            .update_initial_order(auction_id, initial_order)
+            .await;
+        orderbook.insert_orders(auction_id, vec![order]).await;
+        let bid = BidSimulationRequest {
+            sell_amount: U256::from_dec_str("2").unwrap(),
+            buy_amount: U256::from_dec_str("2").unwrap(),
+            user_id: 10_u64,
+        };
+        let bid_order = Order {
+            sell_amount: bid.sell_amount,
+            buy_amount: bid.buy_amount,
+            user_id: bid.user_id,
+        };
+        let filter = simulate_bid(Arc::new(orderbook));
+        let response = request()
+            .path(&format!("/simulate_bid/{:}", auction_id))
+            .method("POST")
+            .json(&bid)
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response_simulation: BidSimulation = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(response_simulation.clearing_order, bid_order);
+        assert_eq!(response_simulation.bidding_amount, U256::from(4));
+        assert!(response_simulation.is_in_the_money);
+        assert!(response_simulation.is_inserted);
+        assert_eq!(
+            response_simulation.order_simulation.fill_status,
+            FillStatus::Filled
+        );
+
+        let zero_bid = BidSimulationRequest {
+            buy_amount: U256::zero(),
+            ..bid
+        };
+        let response = request()
+            .path(&format!("/simulate_bid/{:}", auction_id))
+            .method("POST")
+            .json(&zero_bid)
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let reason: String = serde_json::from_slice(response.body()).unwrap();
+        assert!(reason.contains("buy amount of the bid must be greater than 0"));
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn get_user_orders_without_canceled_or_claimed_() {
         let orderbook = Orderbook::default();
@@ -337,6 +671,56 @@ pub mod test_util {
         assert!(response_details.get(0).unwrap().has_participation);
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn get_user_() {
+        let orderbook = Orderbook::default();
+        let user = User {
+            address: "740a98F8f4fAe0986FB3264Fe4aaCf94ac1EE96f".parse().unwrap(),
+            user_id: 10_u64,
+        };
+        orderbook.insert_users(vec![user]).await;
+        let filter = get_user(Arc::new(orderbook));
+        let response = request()
+            .path(&format!("/get_user/{:}", user.user_id))
+            .method("GET")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response_user: User = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(response_user, user);
+        let response = request()
+            .path("/get_user/11")
+            .method("GET")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn get_user_id_() {
+        let orderbook = Orderbook::default();
+        let user = User {
+            address: "740a98F8f4fAe0986FB3264Fe4aaCf94ac1EE96f".parse().unwrap(),
+            user_id: 10_u64,
+        };
+        orderbook.insert_users(vec![user]).await;
+        let filter = get_user_id(Arc::new(orderbook));
+        let response = request()
+            .path(&format!("/get_user_id/{:}", user.show_full_address()))
+            .method("GET")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response_user_id: u64 = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(response_user_id, user.user_id);
+        let response = request()
+            .path("/get_user_id/0x04668ec2f57cc15c381b461b9fedab5d451c8f7f")
+            .method("GET")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
     #[tokio::test(flavor = "current_thread")]
     #[ignore]
     async fn get_signature_() {
@@ -357,6 +741,7 @@ pub mod test_util {
         let db = Database::new("postgresql://").unwrap();
         db.clear().await.unwrap();
         db.insert_signatures(
+            1,
             auction_id,
             vec![SignaturePackage {
                 user: user.address,
@@ -365,7 +750,7 @@ pub mod test_util {
         )
         .await
         .unwrap();
-        let filter = get_signature(db);
+        let filter = get_signature(db, 1);
         let response = request()
             .path(&format!(
                 "/get_signature/{:}/{:}",
@@ -418,6 +803,7 @@ pub mod test_util {
         assert_eq!(response.status(), StatusCode::OK);
         let signature_from_particular_user = db
             .get_signatures(&SignatureFilter {
+                chain_id: deserialized_signatures.chain_id as u32,
                 auction_id: (deserialized_signatures.auction_id as u32),
                 user_address: Some(user.address),
             })
@@ -429,4 +815,142 @@ pub mod test_util {
             signature_from_particular_user[0]
         );
     }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn get_stats_filtered_by_time_range() {
+        let orderbook = Orderbook::default();
+        for (auction_id, starting_timestamp) in &[(1, 100), (2, 200)] {
+            orderbook
+                .set_auction_details(
+                    *auction_id,
+                    AuctionDetails {
+                        auction_id: *auction_id,
+                        starting_timestamp: *starting_timestamp,
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap();
+        }
+        let filter = get_stats(Arc::new(orderbook));
+        let response = request().path("/stats").method("GET").reply(&filter).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let stats: PlatformStats = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(stats.total_auctions, 2);
+
+        let response = request()
+            .path("/stats?fromTimestamp=150")
+            .method("GET")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let stats: PlatformStats = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(stats.total_auctions, 1);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn get_all_auction_with_details_paginated() {
+        let orderbook = Orderbook::default();
+        let token: H160 = "740a98F8f4fAe0986FB3264Fe4aaCf94ac1EE96f".parse().unwrap();
+        for auction_id in 1..=3 {
+            orderbook
+                .set_auction_details(
+                    auction_id,
+                    AuctionDetails {
+                        auction_id,
+                        interest_score: auction_id as f64,
+                        address_bidding_token: if auction_id == 3 { H160::zero() } else { token },
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap();
+        }
+        let filter = get_all_auction_with_details(Arc::new(orderbook));
+        let auction_ids = |body: &[u8]| {
+            serde_json::from_slice::<Vec<AuctionDetails>>(body)
+                .unwrap()
+                .iter()
+                .map(|details| details.auction_id)
+                .collect::<Vec<_>>()
+        };
+        let response = request()
+            .path("/get_all_auction_with_details")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(auction_ids(response.body()), vec![1, 2, 3]);
+        let response = request()
+            .path(&format!(
+                "/get_all_auction_with_details?token={:?}&sortBy=interestScore&limit=1",
+                token
+            ))
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(auction_ids(response.body()), vec![2]);
+        let response = request()
+            .path("/get_all_auction_with_details?status=finished")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn search_auctions_by_symbol() {
+        let orderbook = Orderbook::default();
+        for (auction_id, symbol, interest_score) in
+            &[(1, "GNO", 1.0), (2, "gno", 2.0), (3, "COW", 3.0)]
+        {
+            orderbook
+                .set_auction_details(
+                    *auction_id,
+                    AuctionDetails {
+                        auction_id: *auction_id,
+                        symbol_auctioning_token: symbol.to_string(),
+                        interest_score: *interest_score,
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap();
+        }
+        let filter = search_auctions(Arc::new(orderbook));
+        let response = request()
+            .path("/search?q=Gno")
+            .method("GET")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let auctions: Vec<AuctionDetails> = serde_json::from_slice(response.body()).unwrap();
+        let auction_ids: Vec<u64> = auctions.iter().map(|details| details.auction_id).collect();
+        assert_eq!(auction_ids, vec![2, 1]);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn get_dead_letters_requires_the_admin_token() {
+        let orderbook = Arc::new(Orderbook::default());
+        let filter = get_dead_letters(orderbook.clone(), Some(AdminToken("secret".to_string())));
+        let get = |authorization: &str| {
+            request()
+                .path("/admin/dead_letters")
+                .method("GET")
+                .header("authorization", authorization)
+        };
+        let response = request()
+            .path("/admin/dead_letters")
+            .method("GET")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = get("Bearer wrong").reply(&filter).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = get("Bearer secret").reply(&filter).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body().as_ref(), b"[]");
+
+        let filter = get_dead_letters(orderbook, None);
+        let response = get("Bearer secret").reply(&filter).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
 }
@@ -1,17 +1,25 @@
 use crate::api::filter::H160Wrapper;
+use crate::api::AdminToken;
 use crate::database::Database;
 use crate::database::SignatureFilter;
 use crate::health::HttpHealthEndpoint;
+use crate::metrics::SyncMetrics;
 use crate::orderbook::Orderbook;
 use futures::future::join_all;
+use futures::stream;
 use futures::TryStreamExt;
-use model::auction_details::AuctionDetails;
+use model::auction_details::{AuctionDetails, AuctionListQuery};
+use model::depth_chart::DepthChartQuery;
 use model::order::Order;
 use model::signature_object::SignaturesObject;
+use model::stats::StatsFilter;
 use model::DomainSeparator;
 use model::Signature;
+use primitive_types::U256;
 use serde::{Deserialize, Serialize};
 use std::{convert::Infallible, sync::Arc};
+use tokio::sync::broadcast::error::RecvError;
+use warp::sse::Event;
 use warp::Filter;
 use warp::Rejection;
 use warp::{
@@ -21,14 +29,38 @@ use warp::{
 
 const MAX_JSON_BODY_PAYLOAD: u64 = 1024 * 10; // rejecting more than 10kbits uploads
 
+/// Bid that is only simulated and not placed in the auction.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BidSimulationRequest {
+    pub sell_amount: U256,
+    pub buy_amount: U256,
+    /// Only used for ordering bids with the same price.
+    #[serde(default)]
+    pub user_id: u64,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct SearchQuery {
+    /// Symbol or address (prefix) of the auctioning or bidding token.
+    pub q: String,
+}
+
+pub fn extract_bid_from_json(
+) -> impl Filter<Extract = (BidSimulationRequest,), Error = Rejection> + Clone {
+    warp::body::content_length_limit(MAX_JSON_BODY_PAYLOAD).and(warp::body::json())
+}
+
 pub fn extract_signatures_object_from_json(
 ) -> impl Filter<Extract = (SignaturesObject,), Error = Rejection> + Clone {
     // (rejecting huge payloads)...
     warp::body::content_length_limit(MAX_JSON_BODY_PAYLOAD).and(warp::body::json())
 }
 
-pub async fn readiness(health: Arc<HttpHealthEndpoint>) -> Result<impl warp::Reply, Infallible> {
-    if health.is_ready() {
+pub async fn readiness(
+    health: Vec<Arc<HttpHealthEndpoint>>,
+) -> Result<impl warp::Reply, Infallible> {
+    if health.iter().all(|health| health.is_ready()) {
         Ok(with_status(json(&""), StatusCode::NO_CONTENT))
     } else {
         Ok(with_status(
@@ -38,13 +70,29 @@ pub async fn readiness(health: Arc<HttpHealthEndpoint>) -> Result<impl warp::Rep
     }
 }
 
+pub async fn get_metrics(
+    metrics: Vec<(u32, Arc<SyncMetrics>)>,
+) -> Result<impl warp::Reply, Infallible> {
+    let metrics: Vec<(u32, &SyncMetrics)> = metrics
+        .iter()
+        .map(|(chain_id, metrics)| (*chain_id, metrics.as_ref()))
+        .collect();
+    Ok(warp::reply::with_header(
+        crate::metrics::encode(&metrics),
+        "content-type",
+        "text/plain; version=0.0.4",
+    ))
+}
+
 pub async fn get_signature(
     auction_id: u64,
     user: H160Wrapper,
     db: Database,
+    chain_id: u32,
 ) -> Result<impl warp::Reply, Infallible> {
     if let Ok(signature) = db
         .get_signatures(&SignatureFilter {
+            chain_id,
             auction_id: (auction_id as u32),
             user_address: Some(user.0),
         })
@@ -68,6 +116,22 @@ pub async fn get_signature(
         ))
     }
 }
+pub async fn get_clearing_price_history(
+    auction_id: u64,
+    db: Database,
+    chain_id: u32,
+) -> Result<impl warp::Reply, Infallible> {
+    match db.load_clearing_prices(chain_id, auction_id).await {
+        Ok(points) => Ok(with_status(json(&points), StatusCode::OK)),
+        Err(err) => Ok(with_status(
+            json(&format!(
+                "Could not retrieve the clearing prices of auction {:}: {:}",
+                auction_id, err
+            )),
+            StatusCode::BAD_REQUEST,
+        )),
+    }
+}
 pub async fn provide_signatures(
     orderbook: Arc<Orderbook>,
     db: Database,
@@ -143,7 +207,11 @@ pub async fn provide_signatures(
         }
     }
     let insert_results = db
-        .insert_signatures(signature_object.auction_id, signature_object.signatures)
+        .insert_signatures(
+            signature_object.chain_id as u32,
+            signature_object.auction_id,
+            signature_object.signatures,
+        )
         .await;
     if let Err(error) = insert_results {
         return Ok(with_status(
@@ -178,6 +246,37 @@ pub async fn get_user_orders(
     Ok(with_status(json(&order), StatusCode::OK))
 }
 
+pub async fn get_user(
+    user_id: u64,
+    orderbook: Arc<Orderbook>,
+) -> Result<impl warp::Reply, Infallible> {
+    match orderbook.get_user(user_id).await {
+        Ok(user) => Ok(with_status(json(&user), StatusCode::OK)),
+        Err(err) => Ok(with_status(
+            json(&format!("{:}", err)),
+            StatusCode::BAD_REQUEST,
+        )),
+    }
+}
+
+pub async fn get_user_id(
+    user: H160Wrapper,
+    orderbook: Arc<Orderbook>,
+) -> Result<impl warp::Reply, Infallible> {
+    match orderbook.get_user_id(user.0).await {
+        // user ids start at 1, 0 is returned for unknown addresses
+        Ok(0) => Ok(with_status(
+            json(&format!("User {:} is not registered", user.0)),
+            StatusCode::BAD_REQUEST,
+        )),
+        Ok(user_id) => Ok(with_status(json(&user_id), StatusCode::OK)),
+        Err(err) => Ok(with_status(
+            json(&format!("{:}", err)),
+            StatusCode::BAD_REQUEST,
+        )),
+    }
+}
+
 pub async fn get_user_orders_without_canceled_or_claimed(
     auction_id: u64,
     user: H160Wrapper,
@@ -206,6 +305,43 @@ pub async fn get_clearing_order_and_volume(
     }
 }
 
+pub async fn simulate_user_orders(
+    auction_id: u64,
+    user: H160Wrapper,
+    orderbook: Arc<Orderbook>,
+) -> Result<impl warp::Reply, Infallible> {
+    let simulations = orderbook.simulate_user_orders(auction_id, user.0).await;
+    match simulations {
+        Ok(simulations) => Ok(with_status(json(&simulations), StatusCode::OK)),
+        Err(err) => Ok(with_status(
+            json(&format!(
+                "Errors: {:?} while simulating the orders of user {:}",
+                err, user.0
+            )),
+            StatusCode::BAD_REQUEST,
+        )),
+    }
+}
+
+pub async fn simulate_bid(
+    auction_id: u64,
+    orderbook: Arc<Orderbook>,
+    bid: BidSimulationRequest,
+) -> Result<impl warp::Reply, Infallible> {
+    let bid = Order {
+        sell_amount: bid.sell_amount,
+        buy_amount: bid.buy_amount,
+        user_id: bid.user_id,
+    };
+    match orderbook.simulate_bid(auction_id, bid).await {
+        Ok(simulation) => Ok(with_status(json(&simulation), StatusCode::OK)),
+        Err(err) => Ok(with_status(
+            json(&format!("Errors: {:?} while simulating the bid", err)),
+            StatusCode::BAD_REQUEST,
+        )),
+    }
+}
+
 pub async fn get_order_book_display_data(
     auction_id: u64,
     orderbook: Arc<Orderbook>,
@@ -220,6 +356,55 @@ pub async fn get_order_book_display_data(
     }
 }
 
+pub async fn get_depth_chart(
+    auction_id: u64,
+    query: DepthChartQuery,
+    orderbook: Arc<Orderbook>,
+) -> Result<impl warp::Reply, Infallible> {
+    match orderbook
+        .get_depth_chart(auction_id, query.number_of_buckets())
+        .await
+    {
+        Ok(depth_chart) => Ok(with_status(json(&depth_chart), StatusCode::OK)),
+        Err(err) => Ok(with_status(
+            json(&format!("{:}", err)),
+            StatusCode::BAD_REQUEST,
+        )),
+    }
+}
+
+/// Server-sent events with the updates of an auction, as they are applied to the orderbook.
+pub async fn stream_auction_updates(
+    auction_id: u64,
+    orderbook: Arc<Orderbook>,
+) -> Result<impl warp::Reply, Infallible> {
+    let receiver = orderbook.update_feed.subscribe();
+    let events = stream::unfold(receiver, move |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(update) if update.auction_id == auction_id => {
+                    return Some((
+                        Event::default().event("update").json_data(&update),
+                        receiver,
+                    ))
+                }
+                Ok(_) => continue,
+                // The client missed updates and needs to reload the orderbook.
+                Err(RecvError::Lagged(number_of_updates)) => {
+                    return Some((
+                        Ok(Event::default()
+                            .event("lagged")
+                            .data(number_of_updates.to_string())),
+                        receiver,
+                    ))
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    Ok(warp::sse::reply(warp::sse::keep_alive().stream(events)))
+}
+
 pub async fn get_details_of_most_interesting_closed_auctions(
     number_of_auctions: u64,
     orderbook: Arc<Orderbook>,
@@ -252,17 +437,43 @@ pub async fn get_details_of_most_interesting_auctions(
     }
 }
 
-pub async fn get_all_auction_with_details(
+pub async fn search_auctions(
+    query: SearchQuery,
     orderbook: Arc<Orderbook>,
 ) -> Result<impl warp::Reply, Infallible> {
-    let auction_detail_data = orderbook.get_all_auction_with_details().await;
-    match auction_detail_data {
-        Err(err) => Ok(with_status(
-            json(&format!("{:}", err)),
-            StatusCode::BAD_REQUEST,
-        )),
-        Ok(auction_detail_data) => Ok(with_status(json(&auction_detail_data), StatusCode::OK)),
+    let auctions = orderbook.search_auctions(&query.q).await;
+    Ok(with_status(json(&auctions), StatusCode::OK))
+}
+
+pub async fn get_stats(
+    filter: StatsFilter,
+    orderbook: Arc<Orderbook>,
+) -> Result<impl warp::Reply, Infallible> {
+    let stats = orderbook.get_stats(filter).await;
+    Ok(with_status(json(&stats), StatusCode::OK))
+}
+
+pub async fn get_dead_letters(
+    admin_token: AdminToken,
+    authorization: Option<String>,
+    orderbook: Arc<Orderbook>,
+) -> Result<impl warp::Reply, Infallible> {
+    if !admin_token.authorizes(authorization.as_deref()) {
+        return Ok(with_status(
+            json(&"Missing or invalid admin token"),
+            StatusCode::UNAUTHORIZED,
+        ));
     }
+    let dead_letters = orderbook.get_dead_letters().await;
+    Ok(with_status(json(&dead_letters), StatusCode::OK))
+}
+
+pub async fn get_all_auction_with_details(
+    query: AuctionListQuery,
+    orderbook: Arc<Orderbook>,
+) -> Result<impl warp::Reply, Infallible> {
+    let auction_detail_data = orderbook.list_auctions(&query).await;
+    Ok(with_status(json(&auction_detail_data), StatusCode::OK))
 }
 pub async fn get_auction_with_details(
     auction_id: u64,
@@ -0,0 +1,291 @@
+//! Bids of a single auction, kept sorted such that placing, cancelling and claiming a bid
+//! as well as finding its predecessor in the queue of the contract take logarithmic time.
+//! The bids accumulated for the clearing price are updated along with the orders as long
+//! as the change allows it, such that they are only accumulated again from the best bid
+//! if an order before the current clearing order changes.
+
+use crate::orderbook::{accumulate_bids, covers_auctioned_amount, AccumulatedBids};
+use anyhow::{anyhow, Result};
+use model::order::Order;
+use std::collections::BTreeSet;
+use std::iter::Peekable;
+
+#[derive(Clone, Debug, Default)]
+pub struct AuctionOrders {
+    /// Orders, which were not claimed yet.
+    open: BTreeSet<Order>,
+    /// Orders, which were claimed after the settlement. They are disjoint from the open ones.
+    claimed: BTreeSet<Order>,
+    /// Accumulated bids for the initial order of the auction.
+    accumulated_bids: Option<(Order, AccumulatedBids)>,
+}
+
+impl AuctionOrders {
+    /// Inserts an open order. Returns false, if the order is already contained.
+    pub fn insert(&mut self, order: Order) -> bool {
+        if self.claimed.contains(&order) || !self.open.insert(order) {
+            return false;
+        }
+        self.update_accumulated_bids_on_insert(&order);
+        true
+    }
+
+    /// Removes an order, regardless whether it was claimed. Returns false, if the order is
+    /// not contained.
+    pub fn remove(&mut self, order: &Order) -> bool {
+        if !self.open.remove(order) && !self.claimed.remove(order) {
+            return false;
+        }
+        self.update_accumulated_bids_on_remove(order);
+        true
+    }
+
+    /// Marks an open order as claimed. Returns false, if the order is not open.
+    pub fn claim(&mut self, order: &Order) -> bool {
+        if !self.open.remove(order) {
+            return false;
+        }
+        self.claimed.insert(*order)
+    }
+
+    /// Marks a claimed order as open again. Returns false, if the order is not claimed.
+    pub fn unclaim(&mut self, order: &Order) -> bool {
+        if !self.claimed.remove(order) {
+            return false;
+        }
+        self.open.insert(*order)
+    }
+
+    pub fn is_open(&self, order: &Order) -> bool {
+        self.open.contains(order)
+    }
+
+    pub fn is_claimed(&self, order: &Order) -> bool {
+        self.claimed.contains(order)
+    }
+
+    pub fn len(&self) -> usize {
+        self.open.len() + self.claimed.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.open.is_empty() && self.claimed.is_empty()
+    }
+
+    /// All orders in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = &Order> + '_ {
+        Merge {
+            left: self.open.iter().peekable(),
+            right: self.claimed.iter().peekable(),
+        }
+    }
+
+    /// Orders, which were not claimed yet, in ascending order.
+    pub fn open_orders(&self) -> impl Iterator<Item = &Order> + '_ {
+        self.open.iter()
+    }
+
+    /// Largest open order smaller than the given order.
+    pub fn previous_open_order(&self, order: &Order) -> Option<&Order> {
+        self.open.range(..order).next_back()
+    }
+
+    fn last_order(&self) -> Option<&Order> {
+        self.open
+            .iter()
+            .next_back()
+            .max(self.claimed.iter().next_back())
+    }
+
+    /// Bids accumulated for the clearing price, which are only accumulated from all orders,
+    /// if they are not up to date.
+    pub fn accumulated_bids(&self, initial_order: Order) -> Result<AccumulatedBids> {
+        match self.accumulated_bids {
+            Some((order, accumulated_bids)) if order == initial_order => Ok(accumulated_bids),
+            _ => accumulate_bids(self.iter(), initial_order),
+        }
+    }
+
+    /// Accumulates the bids for the clearing price, if they are not up to date, and keeps
+    /// them up to date with the following changes.
+    pub fn update_accumulated_bids(&mut self, initial_order: Order) -> Result<AccumulatedBids> {
+        let accumulated_bids = self.accumulated_bids(initial_order)?;
+        self.accumulated_bids = Some((initial_order, accumulated_bids));
+        Ok(accumulated_bids)
+    }
+
+    fn update_accumulated_bids_on_insert(&mut self, order: &Order) {
+        let (initial_order, accumulated_bids) = match &mut self.accumulated_bids {
+            Some((initial_order, accumulated_bids)) => (*initial_order, accumulated_bids),
+            None => return,
+        };
+        if *order < accumulated_bids.last_order {
+            // the order is accumulated before the last order, which changes all later sums
+            self.accumulated_bids = None;
+        } else if !accumulated_bids.covered {
+            // all bids were used, hence the order is accumulated next
+            let updated = accumulated_bids
+                .bid_sum
+                .checked_add(order.sell_amount)
+                .ok_or_else(|| anyhow!("bid sum overflows"))
+                .and_then(|bid_sum| {
+                    Ok(AccumulatedBids {
+                        last_order: *order,
+                        bid_sum,
+                        covered: covers_auctioned_amount(bid_sum, order, &initial_order)?,
+                    })
+                });
+            match updated {
+                Ok(updated) => *accumulated_bids = updated,
+                Err(_) => self.accumulated_bids = None,
+            }
+        }
+        // otherwise, the order comes after the covering bids and is not accumulated
+    }
+
+    fn update_accumulated_bids_on_remove(&mut self, order: &Order) {
+        let last_order = self.last_order().copied();
+        let accumulated_bids = match &mut self.accumulated_bids {
+            Some((_, accumulated_bids)) => accumulated_bids,
+            None => return,
+        };
+        if accumulated_bids.covered {
+            if *order <= accumulated_bids.last_order {
+                self.accumulated_bids = None;
+            }
+            // otherwise, the order came after the covering bids and was not accumulated
+        } else {
+            // All bids were used without covering the auctioned amount, which stays so
+            // with fewer bids.
+            accumulated_bids.bid_sum = accumulated_bids.bid_sum.saturating_sub(order.sell_amount);
+            accumulated_bids.last_order = last_order.unwrap_or_default();
+        }
+    }
+}
+
+/// Merges two ascending iterators into one ascending iterator.
+struct Merge<'a, L: Iterator<Item = &'a Order>, R: Iterator<Item = &'a Order>> {
+    left: Peekable<L>,
+    right: Peekable<R>,
+}
+
+impl<'a, L: Iterator<Item = &'a Order>, R: Iterator<Item = &'a Order>> Iterator
+    for Merge<'a, L, R>
+{
+    type Item = &'a Order;
+
+    fn next(&mut self) -> Option<&'a Order> {
+        match (self.left.peek(), self.right.peek()) {
+            (Some(left), Some(right)) if right < left => self.right.next(),
+            (Some(_), _) => self.left.next(),
+            (None, _) => self.right.next(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitive_types::U256;
+
+    fn order(buy_amount: u64) -> Order {
+        Order {
+            sell_amount: U256::from(10),
+            buy_amount: U256::from(buy_amount),
+            user_id: 1,
+        }
+    }
+
+    #[test]
+    fn keeps_orders_sorted_across_claims() {
+        let mut orders = AuctionOrders::default();
+        for buy_amount in &[4, 1, 3, 2] {
+            assert!(orders.insert(order(*buy_amount)));
+        }
+        assert!(!orders.insert(order(3)));
+        assert!(orders.claim(&order(3)));
+        assert!(!orders.insert(order(3)));
+        assert!(!orders.claim(&order(3)));
+
+        let mut expected = vec![order(1), order(2), order(3), order(4)];
+        expected.sort();
+        assert_eq!(orders.iter().copied().collect::<Vec<_>>(), expected);
+        expected.retain(|expected_order| *expected_order != order(3));
+        assert_eq!(orders.open_orders().copied().collect::<Vec<_>>(), expected);
+        assert_eq!(orders.len(), 4);
+
+        assert!(orders.unclaim(&order(3)));
+        assert!(orders.is_open(&order(3)));
+        assert!(orders.claim(&order(3)));
+        assert!(orders.remove(&order(3)));
+        assert!(!orders.is_claimed(&order(3)));
+        assert!(!orders.remove(&order(3)));
+        assert_eq!(orders.len(), 3);
+    }
+
+    #[test]
+    fn finds_previous_open_order() {
+        let mut orders = AuctionOrders::default();
+        let mut sorted = vec![order(1), order(2), order(3)];
+        sorted.sort();
+        for order in &sorted {
+            orders.insert(*order);
+        }
+        assert_eq!(orders.previous_open_order(&sorted[0]), None);
+        assert_eq!(orders.previous_open_order(&sorted[2]), Some(&sorted[1]));
+        orders.claim(&sorted[1]);
+        assert_eq!(orders.previous_open_order(&sorted[2]), Some(&sorted[0]));
+    }
+
+    #[test]
+    fn keeps_accumulated_bids_up_to_date() {
+        let initial_order = Order {
+            sell_amount: U256::from(50),
+            buy_amount: U256::from(1),
+            user_id: 0,
+        };
+        let bid = |sell_amount: u64, buy_amount: u64| Order {
+            sell_amount: U256::from(sell_amount),
+            buy_amount: U256::from(buy_amount),
+            user_id: 1,
+        };
+        let mut orders = AuctionOrders::default();
+        let assert_up_to_date = |orders: &AuctionOrders| {
+            assert_eq!(
+                orders.accumulated_bids(initial_order).unwrap(),
+                accumulate_bids(orders.iter(), initial_order).unwrap()
+            );
+        };
+        orders.update_accumulated_bids(initial_order).unwrap();
+        assert!(orders.accumulated_bids.is_some());
+
+        // bids, which do not cover the auctioned amount, are accumulated one by one
+        orders.insert(bid(10, 15));
+        orders.insert(bid(10, 20));
+        assert!(orders.accumulated_bids.is_some());
+        assert_up_to_date(&orders);
+        orders.remove(&bid(10, 20));
+        assert!(orders.accumulated_bids.is_some());
+        assert_up_to_date(&orders);
+        orders.insert(bid(10, 20));
+        orders.insert(bid(100, 250));
+        assert!(orders.accumulated_bids.unwrap().1.covered);
+        assert_up_to_date(&orders);
+
+        // bids after the covering bid are not accumulated
+        orders.insert(bid(10, 30));
+        orders.claim(&bid(10, 30));
+        orders.remove(&bid(10, 30));
+        assert!(orders.accumulated_bids.is_some());
+        assert_up_to_date(&orders);
+
+        // bids before the covering bid require another accumulation
+        orders.insert(bid(10, 5));
+        assert!(orders.accumulated_bids.is_none());
+        assert_up_to_date(&orders);
+        orders.update_accumulated_bids(initial_order).unwrap();
+        orders.remove(&bid(10, 20));
+        assert!(orders.accumulated_bids.is_none());
+        assert_up_to_date(&orders);
+    }
+}
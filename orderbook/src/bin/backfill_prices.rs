@@ -0,0 +1,84 @@
+//! Stores the USD prices of the bidding tokens of all persisted auctions of a chain at the
+//! days the auctions end, such that the orderbook does not need to query them on start-up.
+
+use orderbook::database::Database;
+use orderbook::network_config::load_network_configs;
+use orderbook::price_oracle::{CachedPriceOracle, PriceOracles};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use structopt::StructOpt;
+use url::Url;
+
+#[derive(Debug, StructOpt)]
+struct Arguments {
+    #[structopt(long, env = "LOG_FILTER", default_value = "warn,backfill_prices=info")]
+    pub log_filter: String,
+
+    /// Url of the Postgres database. By default connects to locally running postgres.
+    #[structopt(long, env = "DB_URL", default_value = "postgresql://")]
+    db_url: Url,
+
+    /// The Ethereum node URL of the chain, whose auctions are valued.
+    #[structopt(long, env = "NODE_URL", default_value = "https://rpc.ankr.com/eth")]
+    pub node_url: Url,
+
+    /// Path to a JSON file configuring the networks. Defaults to the officially supported
+    /// networks.
+    #[structopt(long, env = "NETWORK_CONFIG")]
+    pub network_config: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Arguments::from_args();
+    tracing_setup::initialize(args.log_filter.as_str());
+    let database = Database::new(args.db_url.as_str()).expect("failed to create database");
+    let transport =
+        web3::transports::Http::new(args.node_url.as_str()).expect("transport creation failed");
+    let web3 = web3::Web3::new(transport);
+    let chain_id = web3
+        .eth()
+        .chain_id()
+        .await
+        .expect("Couldn't load chain id")
+        .as_u32();
+    let network = load_network_configs(args.network_config.as_deref())
+        .expect("loading the network config failed")
+        .remove(&chain_id)
+        .unwrap_or_else(|| panic!("chain id {} is not configured", chain_id));
+    let price_oracle = CachedPriceOracle::new(
+        PriceOracles::from_configs(&network.price_oracles, &web3)
+            .expect("creating the price oracles failed"),
+        database.clone(),
+        chain_id,
+    );
+
+    let auctions = database
+        .load_orderbook(chain_id)
+        .await
+        .expect("loading the persisted orderbook failed")
+        .auctions;
+    let prices_to_backfill: BTreeSet<_> = auctions
+        .iter()
+        .filter(|details| {
+            !network
+                .stable_coins
+                .contains(&details.address_bidding_token)
+        })
+        .map(|details| (details.address_bidding_token, details.end_time_timestamp))
+        .collect();
+    let mut backfilled = 0;
+    for (token, timestamp) in prices_to_backfill {
+        match price_oracle.backfill(token, timestamp).await {
+            Ok(true) => backfilled += 1,
+            Ok(false) => (),
+            Err(err) => tracing::warn!(?token, timestamp, "backfilling price failed: {:?}", err),
+        }
+    }
+    tracing::info!(
+        "backfilled {} prices for {} auctions of chain {}",
+        backfilled,
+        auctions.len(),
+        chain_id
+    );
+}
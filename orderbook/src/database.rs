@@ -1,8 +1,13 @@
+mod clearing_prices;
+mod dead_letters;
+mod orderbook_state;
 mod signatures;
+mod token_prices;
 
 use anyhow::Result;
 use sqlx::PgPool;
 
+pub use orderbook_state::*;
 pub use signatures::*;
 
 // The pool uses an Arc internally.
@@ -23,7 +28,10 @@ impl Database {
     pub async fn clear(&self) -> Result<()> {
         use sqlx::Executor;
         self.pool
-            .execute(sqlx::query("TRUNCATE signatures;"))
+            .execute(sqlx::query(
+                "TRUNCATE signatures, auctions, orders, users, sync_cursor, sync_checkpoints, token_prices, \
+                 dead_letters, clearing_prices;",
+            ))
             .await?;
         Ok(())
     }
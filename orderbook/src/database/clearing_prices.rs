@@ -0,0 +1,113 @@
+use super::*;
+use anyhow::Context;
+use model::auction_details::ClearingPricePoint;
+use model::decimal::Decimal;
+use std::str::FromStr;
+
+impl Database {
+    /// Stores the clearing price points of several auctions.
+    pub async fn insert_clearing_prices(
+        &self,
+        chain_id: u32,
+        points: &[(u64, ClearingPricePoint)],
+    ) -> Result<()> {
+        const QUERY: &str = "\
+            INSERT INTO clearing_prices \
+                (chain_id, auction_id, block_number, timestamp, clearing_price, bidding_amount) \
+            VALUES ($1, $2, $3, $4, $5::numeric, $6::numeric) \
+            ON CONFLICT (chain_id, auction_id, block_number) DO UPDATE SET \
+                timestamp = EXCLUDED.timestamp, clearing_price = EXCLUDED.clearing_price, \
+                bidding_amount = EXCLUDED.bidding_amount;";
+        let mut transaction = self.pool.begin().await?;
+        for (auction_id, point) in points {
+            sqlx::query(QUERY)
+                .bind(chain_id as i64)
+                .bind(*auction_id as i64)
+                .bind(point.block_number as i64)
+                .bind(point.timestamp as i64)
+                .bind(point.clearing_price.to_string())
+                .bind(point.bidding_amount.to_string())
+                .execute(&mut transaction)
+                .await
+                .context("insert_clearing_prices failed")?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    /// Clearing price points of an auction, ordered by their block.
+    pub async fn load_clearing_prices(
+        &self,
+        chain_id: u32,
+        auction_id: u64,
+    ) -> Result<Vec<ClearingPricePoint>> {
+        const QUERY: &str = "\
+            SELECT block_number, timestamp, clearing_price::text, bidding_amount::text \
+            FROM clearing_prices WHERE chain_id = $1 AND auction_id = $2 \
+            ORDER BY block_number;";
+        let rows: Vec<(i64, i64, String, String)> = sqlx::query_as(QUERY)
+            .bind(chain_id as i64)
+            .bind(auction_id as i64)
+            .fetch_all(&self.pool)
+            .await
+            .context("load_clearing_prices failed")?;
+        rows.into_iter()
+            .map(
+                |(block_number, timestamp, clearing_price, bidding_amount)| {
+                    Ok(ClearingPricePoint {
+                        block_number: block_number as u64,
+                        timestamp: timestamp as u64,
+                        clearing_price: Decimal::from_str(&clearing_price)?,
+                        bidding_amount: Decimal::from_str(&bidding_amount)?,
+                    })
+                },
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::AppliedUpdates;
+
+    #[tokio::test(flavor = "current_thread")]
+    #[ignore]
+    async fn postgres_clearing_prices_roundtrip() {
+        let db = Database::new("postgresql://").unwrap();
+        db.clear().await.unwrap();
+        let point = |block_number, clearing_price: &str| ClearingPricePoint {
+            block_number,
+            timestamp: block_number * 15,
+            clearing_price: Decimal::from_str(clearing_price).unwrap(),
+            bidding_amount: Decimal::from_str("1.000000000000000001").unwrap(),
+        };
+        db.insert_clearing_prices(1, &[(1, point(12, "2.5")), (2, point(12, "1"))])
+            .await
+            .unwrap();
+        db.insert_clearing_prices(1, &[(1, point(10, "2"))])
+            .await
+            .unwrap();
+        assert_eq!(
+            db.load_clearing_prices(1, 1).await.unwrap(),
+            vec![point(10, "2"), point(12, "2.5")]
+        );
+        assert!(db.load_clearing_prices(4, 1).await.unwrap().is_empty());
+
+        db.revert_updates(
+            1,
+            &[AppliedUpdates::default()],
+            SyncCursor {
+                last_block: 11,
+                block_hash: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            db.load_clearing_prices(1, 1).await.unwrap(),
+            vec![point(10, "2")]
+        );
+        assert!(db.load_clearing_prices(1, 2).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+use super::*;
+use crate::dead_letters::{DeadLetter, DeadLetterKind};
+use anyhow::{anyhow, Context};
+
+impl Database {
+    pub async fn upsert_dead_letter(&self, chain_id: u32, dead_letter: &DeadLetter) -> Result<()> {
+        const QUERY: &str = "\
+            INSERT INTO dead_letters \
+                (chain_id, auction_id, kind, block_number, error, attempts, next_retry) \
+            VALUES ($1, $2, $3, $4, $5, $6, $7) \
+            ON CONFLICT (chain_id, auction_id) DO UPDATE SET \
+                kind = EXCLUDED.kind, block_number = EXCLUDED.block_number, \
+                error = EXCLUDED.error, attempts = EXCLUDED.attempts, \
+                next_retry = EXCLUDED.next_retry;";
+        sqlx::query(QUERY)
+            .bind(chain_id as i64)
+            .bind(dead_letter.auction_id as i64)
+            .bind(dead_letter.kind.as_str())
+            .bind(dead_letter.block_number as i64)
+            .bind(&dead_letter.error)
+            .bind(dead_letter.attempts as i64)
+            .bind(dead_letter.next_retry as i64)
+            .execute(&self.pool)
+            .await
+            .context("upsert_dead_letter failed")?;
+        Ok(())
+    }
+
+    pub async fn delete_dead_letter(&self, chain_id: u32, auction_id: u64) -> Result<()> {
+        const QUERY: &str = "DELETE FROM dead_letters WHERE chain_id = $1 AND auction_id = $2;";
+        sqlx::query(QUERY)
+            .bind(chain_id as i64)
+            .bind(auction_id as i64)
+            .execute(&self.pool)
+            .await
+            .context("delete_dead_letter failed")?;
+        Ok(())
+    }
+
+    /// Deletes the dead letters of events after the given block.
+    pub async fn delete_dead_letters_after(&self, chain_id: u32, block_number: u64) -> Result<()> {
+        const QUERY: &str = "DELETE FROM dead_letters WHERE chain_id = $1 AND block_number > $2;";
+        sqlx::query(QUERY)
+            .bind(chain_id as i64)
+            .bind(block_number as i64)
+            .execute(&self.pool)
+            .await
+            .context("delete_dead_letters_after failed")?;
+        Ok(())
+    }
+
+    pub async fn load_dead_letters(&self, chain_id: u32) -> Result<Vec<DeadLetter>> {
+        const QUERY: &str = "\
+            SELECT auction_id, kind, block_number, error, attempts, next_retry \
+            FROM dead_letters WHERE chain_id = $1;";
+        let rows: Vec<(i64, String, i64, String, i64, i64)> = sqlx::query_as(QUERY)
+            .bind(chain_id as i64)
+            .fetch_all(&self.pool)
+            .await
+            .context("load_dead_letters failed")?;
+        rows.into_iter()
+            .map(
+                |(auction_id, kind, block_number, error, attempts, next_retry)| {
+                    Ok(DeadLetter {
+                        auction_id: auction_id as u64,
+                        kind: DeadLetterKind::parse(&kind)
+                            .ok_or_else(|| anyhow!("unknown dead letter kind {}", kind))?,
+                        block_number: block_number as u64,
+                        error,
+                        attempts: attempts as u32,
+                        next_retry: next_retry as u64,
+                    })
+                },
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "current_thread")]
+    #[ignore]
+    async fn postgres_dead_letter_roundtrip() {
+        let db = Database::new("postgresql://").unwrap();
+        db.clear().await.unwrap();
+        let mut dead_letter =
+            DeadLetter::new(1, DeadLetterKind::Creation, 10, "timeout".to_string(), 1000);
+        db.upsert_dead_letter(1, &dead_letter).await.unwrap();
+        let clearing = DeadLetter::new(
+            2,
+            DeadLetterKind::Clearing,
+            20,
+            "reverted".to_string(),
+            1000,
+        );
+        db.upsert_dead_letter(1, &clearing).await.unwrap();
+        dead_letter.record_failure("timeout again".to_string(), 2000);
+        db.upsert_dead_letter(1, &dead_letter).await.unwrap();
+
+        let mut dead_letters = db.load_dead_letters(1).await.unwrap();
+        dead_letters.sort_by_key(|dead_letter| dead_letter.auction_id);
+        assert_eq!(dead_letters.len(), 2);
+        assert_eq!(dead_letters[0], dead_letter);
+        assert_eq!(dead_letters[1], clearing);
+        assert!(db.load_dead_letters(4).await.unwrap().is_empty());
+
+        db.delete_dead_letters_after(1, 15).await.unwrap();
+        assert_eq!(db.load_dead_letters(1).await.unwrap(), vec![dead_letter]);
+        db.delete_dead_letter(1, 1).await.unwrap();
+        assert!(db.load_dead_letters(1).await.unwrap().is_empty());
+    }
+}
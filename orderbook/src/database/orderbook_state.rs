@@ -0,0 +1,519 @@
+use super::*;
+use crate::event_reader::{OrderEventKind, MAX_CHECKPOINTS};
+use crate::orderbook::AppliedUpdates;
+use anyhow::{anyhow, Context, Result};
+use model::auction_details::{AuctionDetails, AuctionSettlement};
+use model::order::Order;
+use model::user::User;
+use primitive_types::{H160, H256};
+use std::convert::TryInto;
+
+/// Orderbook state as it was persisted up to `last_block`.
+#[derive(Debug, Default)]
+pub struct PersistedOrderbook {
+    pub auctions: Vec<AuctionDetails>,
+    /// (auction_id, order, claimed)
+    pub orders: Vec<(u64, Order, bool)>,
+    pub users: Vec<User>,
+    /// `None` means that nothing was synced yet for the chain.
+    pub cursor: Option<SyncCursor>,
+    /// Updates of the last `MAX_CHECKPOINTS` synced block ranges, the most recent ones last.
+    pub checkpoints: Vec<AppliedUpdates>,
+}
+
+/// Last block whose events are contained in the persisted state.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SyncCursor {
+    pub last_block: u64,
+    /// `None` for cursors that were written before block hashes were tracked.
+    pub block_hash: Option<H256>,
+}
+
+const INSERT_ORDER: &str = "\
+    INSERT INTO orders (chain_id, auction_id, encoded_order) \
+    VALUES ($1, $2, $3) \
+    ON CONFLICT (chain_id, auction_id, encoded_order) DO NOTHING;";
+const DELETE_ORDER: &str = "\
+    DELETE FROM orders \
+    WHERE chain_id = $1 AND auction_id = $2 AND encoded_order = $3;";
+const UPDATE_SETTLEMENT: &str = "\
+    UPDATE auctions SET details = details || $3::jsonb \
+    WHERE chain_id = $1 AND auction_id = $2;";
+const UPDATE_CURSOR: &str = "\
+    INSERT INTO sync_cursor (chain_id, last_block, block_hash) \
+    VALUES ($1, $2, $3) \
+    ON CONFLICT (chain_id) DO UPDATE \
+    SET last_block = EXCLUDED.last_block, block_hash = EXCLUDED.block_hash;";
+
+impl Database {
+    /// Stores all updates of a synced block range together with the new sync cursor and the
+    /// checkpoint of the range. The updates are written in a single transaction, hence the
+    /// cursor never points to a block whose events are only partially stored.
+    pub async fn persist_updates(
+        &self,
+        chain_id: u32,
+        new_auctions: &[AuctionDetails],
+        updates: &AppliedUpdates,
+    ) -> Result<()> {
+        const INSERT_AUCTION: &str = "\
+            INSERT INTO auctions (chain_id, auction_id, details) \
+            VALUES ($1, $2, $3::jsonb) \
+            ON CONFLICT (chain_id, auction_id) DO UPDATE SET details = EXCLUDED.details;";
+        const CLAIM_ORDER: &str = "\
+            UPDATE orders SET claimed = true \
+            WHERE chain_id = $1 AND auction_id = $2 AND encoded_order = $3;";
+        const INSERT_USER: &str = "\
+            INSERT INTO users (chain_id, user_id, user_address) \
+            VALUES ($1, $2, $3) \
+            ON CONFLICT (chain_id, user_id) DO NOTHING;";
+        const INSERT_CHECKPOINT: &str = "\
+            INSERT INTO sync_checkpoints (chain_id, block_number, block_hash, updates) \
+            VALUES ($1, $2, $3, $4::jsonb) \
+            ON CONFLICT (chain_id, block_number) DO UPDATE \
+            SET block_hash = EXCLUDED.block_hash, updates = EXCLUDED.updates;";
+
+        let order_updates = &updates.order_updates;
+        let chain_id = chain_id as i64;
+        let mut transaction = self.pool.begin().await?;
+        for details in new_auctions {
+            sqlx::query(INSERT_AUCTION)
+                .bind(chain_id)
+                .bind(details.auction_id as i64)
+                .bind(serde_json::to_string(details)?)
+                .execute(&mut transaction)
+                .await
+                .context("insert_auction failed")?;
+        }
+        for clearing in &order_updates.auctions_cleared {
+            sqlx::query(UPDATE_SETTLEMENT)
+                .bind(chain_id)
+                .bind(clearing.auction_id as i64)
+                .bind(serde_json::to_string(&clearing.settlement)?)
+                .execute(&mut transaction)
+                .await
+                .context("update_settlement failed")?;
+        }
+        for event in &order_updates.order_events {
+            let query = match event.kind {
+                OrderEventKind::Placed => INSERT_ORDER,
+                OrderEventKind::Cancelled => DELETE_ORDER,
+                OrderEventKind::Claimed => CLAIM_ORDER,
+            };
+            sqlx::query(query)
+                .bind(chain_id)
+                .bind(event.auction_id as i64)
+                .bind(event.order.convert_to_bytes().to_vec())
+                .execute(&mut transaction)
+                .await
+                .context("persisting order update failed")?;
+        }
+        for user in &order_updates.users_added {
+            sqlx::query(INSERT_USER)
+                .bind(chain_id)
+                .bind(user.user_id as i64)
+                .bind(user.address.as_bytes())
+                .execute(&mut transaction)
+                .await
+                .context("insert_user failed")?;
+        }
+        sqlx::query(INSERT_CHECKPOINT)
+            .bind(chain_id)
+            .bind(order_updates.last_block_handled as i64)
+            .bind(updates.block_hash.as_bytes())
+            .bind(serde_json::to_string(updates)?)
+            .execute(&mut transaction)
+            .await
+            .context("insert_checkpoint failed")?;
+        sqlx::query(UPDATE_CURSOR)
+            .bind(chain_id)
+            .bind(order_updates.last_block_handled as i64)
+            .bind(updates.block_hash.as_bytes())
+            .execute(&mut transaction)
+            .await
+            .context("update_sync_cursor failed")?;
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    /// Reverts the given updates, most recent ones last, in a single transaction and
+    /// resets the sync cursor to the last block that is still contained in the state.
+    pub async fn revert_updates(
+        &self,
+        chain_id: u32,
+        reverted_updates: &[AppliedUpdates],
+        cursor: SyncCursor,
+    ) -> Result<()> {
+        const DELETE_AUCTION: &str = "\
+            DELETE FROM auctions WHERE chain_id = $1 AND auction_id = $2;";
+        const DELETE_AUCTION_ORDERS: &str = "\
+            DELETE FROM orders WHERE chain_id = $1 AND auction_id = $2;";
+        const UNCLAIM_ORDER: &str = "\
+            UPDATE orders SET claimed = false \
+            WHERE chain_id = $1 AND auction_id = $2 AND encoded_order = $3;";
+        const DELETE_USER: &str = "\
+            DELETE FROM users WHERE chain_id = $1 AND user_id = $2;";
+        const DELETE_CLEARING_PRICES: &str = "\
+            DELETE FROM clearing_prices WHERE chain_id = $1 AND block_number > $2;";
+        const DELETE_CHECKPOINTS: &str = "\
+            DELETE FROM sync_checkpoints WHERE chain_id = $1 AND block_number > $2;";
+
+        let chain_id = chain_id as i64;
+        let mut transaction = self.pool.begin().await?;
+        for updates in reverted_updates.iter().rev() {
+            let order_updates = &updates.order_updates;
+            for clearing in &order_updates.auctions_cleared {
+                sqlx::query(UPDATE_SETTLEMENT)
+                    .bind(chain_id)
+                    .bind(clearing.auction_id as i64)
+                    .bind(serde_json::to_string(&AuctionSettlement::default())?)
+                    .execute(&mut transaction)
+                    .await
+                    .context("update_settlement failed")?;
+            }
+            for event in order_updates.order_events.iter().rev() {
+                let query = match event.kind {
+                    OrderEventKind::Placed => DELETE_ORDER,
+                    OrderEventKind::Cancelled => INSERT_ORDER,
+                    OrderEventKind::Claimed => UNCLAIM_ORDER,
+                };
+                sqlx::query(query)
+                    .bind(chain_id)
+                    .bind(event.auction_id as i64)
+                    .bind(event.order.convert_to_bytes().to_vec())
+                    .execute(&mut transaction)
+                    .await
+                    .context("reverting order update failed")?;
+            }
+            for user in &order_updates.users_added {
+                sqlx::query(DELETE_USER)
+                    .bind(chain_id)
+                    .bind(user.user_id as i64)
+                    .execute(&mut transaction)
+                    .await
+                    .context("delete_user failed")?;
+            }
+            for auction_id in &updates.new_auctions {
+                for query in [DELETE_AUCTION_ORDERS, DELETE_AUCTION].iter() {
+                    sqlx::query(query)
+                        .bind(chain_id)
+                        .bind(*auction_id as i64)
+                        .execute(&mut transaction)
+                        .await
+                        .context("delete_auction failed")?;
+                }
+            }
+        }
+        sqlx::query(DELETE_CLEARING_PRICES)
+            .bind(chain_id)
+            .bind(cursor.last_block as i64)
+            .execute(&mut transaction)
+            .await
+            .context("delete_clearing_prices failed")?;
+        sqlx::query(DELETE_CHECKPOINTS)
+            .bind(chain_id)
+            .bind(cursor.last_block as i64)
+            .execute(&mut transaction)
+            .await
+            .context("delete_checkpoints failed")?;
+        sqlx::query(UPDATE_CURSOR)
+            .bind(chain_id)
+            .bind(cursor.last_block as i64)
+            .bind(cursor.block_hash.as_ref().map(|hash| hash.as_bytes()))
+            .execute(&mut transaction)
+            .await
+            .context("update_sync_cursor failed")?;
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    /// Deletes the complete persisted state of a chain, such that it is synced again
+    /// from the beginning.
+    pub async fn delete_orderbook(&self, chain_id: u32) -> Result<()> {
+        let mut transaction = self.pool.begin().await?;
+        for table in [
+            "auctions",
+            "orders",
+            "users",
+            "sync_cursor",
+            "sync_checkpoints",
+            "dead_letters",
+            "clearing_prices",
+        ]
+        .iter()
+        {
+            sqlx::query(&format!("DELETE FROM {} WHERE chain_id = $1;", table))
+                .bind(chain_id as i64)
+                .execute(&mut transaction)
+                .await
+                .context("delete_orderbook failed")?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    pub async fn load_orderbook(&self, chain_id: u32) -> Result<PersistedOrderbook> {
+        const AUCTIONS: &str = "SELECT details::text FROM auctions WHERE chain_id = $1;";
+        const ORDERS: &str =
+            "SELECT auction_id, encoded_order, claimed FROM orders WHERE chain_id = $1;";
+        const USERS: &str = "SELECT user_id, user_address FROM users WHERE chain_id = $1;";
+        const CURSOR: &str = "SELECT last_block, block_hash FROM sync_cursor WHERE chain_id = $1;";
+        const LAST_CHECKPOINTS: &str = "\
+            SELECT updates::text FROM sync_checkpoints WHERE chain_id = $1 \
+            ORDER BY block_number DESC LIMIT $2;";
+
+        let chain_id = chain_id as i64;
+        let auctions = sqlx::query_as(AUCTIONS)
+            .bind(chain_id)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|(details,): (String,)| -> Result<AuctionDetails> {
+                Ok(serde_json::from_str(&details)?)
+            })
+            .collect::<Result<Vec<AuctionDetails>>>()?;
+        let orders = sqlx::query_as(ORDERS)
+            .bind(chain_id)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|row: OrdersQueryRow| row.into_order())
+            .collect::<Result<Vec<_>>>()?;
+        let users = sqlx::query_as(USERS)
+            .bind(chain_id)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|row: UsersQueryRow| row.into_user())
+            .collect::<Result<Vec<_>>>()?;
+        let cursor: Option<(i64, Option<Vec<u8>>)> = sqlx::query_as(CURSOR)
+            .bind(chain_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        let cursor = match cursor {
+            Some((last_block, block_hash)) => {
+                let block_hash = match block_hash {
+                    Some(block_hash) if block_hash.len() == 32 => {
+                        Some(H256::from_slice(&block_hash))
+                    }
+                    Some(_) => return Err(anyhow!("block hash has wrong length")),
+                    None => None,
+                };
+                Some(SyncCursor {
+                    last_block: last_block as u64,
+                    block_hash,
+                })
+            }
+            None => None,
+        };
+        let mut checkpoints = sqlx::query_as(LAST_CHECKPOINTS)
+            .bind(chain_id)
+            .bind(MAX_CHECKPOINTS as i64)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|(updates,): (String,)| -> Result<AppliedUpdates> {
+                Ok(serde_json::from_str(&updates)?)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        checkpoints.reverse();
+        Ok(PersistedOrderbook {
+            auctions,
+            orders,
+            users,
+            cursor,
+            checkpoints,
+        })
+    }
+
+    /// Block numbers and hashes of all persisted checkpoints, the most recent ones last.
+    pub async fn load_checkpoint_hashes(&self, chain_id: u32) -> Result<Vec<(u64, H256)>> {
+        const CHECKPOINT_HASHES: &str = "\
+            SELECT block_number, block_hash FROM sync_checkpoints WHERE chain_id = $1 \
+            ORDER BY block_number;";
+
+        sqlx::query_as(CHECKPOINT_HASHES)
+            .bind(chain_id as i64)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|(block_number, block_hash): (i64, Vec<u8>)| {
+                if block_hash.len() != 32 {
+                    return Err(anyhow!("block hash has wrong length"));
+                }
+                Ok((block_number as u64, H256::from_slice(&block_hash)))
+            })
+            .collect()
+    }
+
+    /// Updates of the synced block ranges after the given block, the most recent ones last.
+    pub async fn load_checkpoints_after(
+        &self,
+        chain_id: u32,
+        block_number: u64,
+    ) -> Result<Vec<AppliedUpdates>> {
+        const CHECKPOINTS_AFTER: &str = "\
+            SELECT updates::text FROM sync_checkpoints WHERE chain_id = $1 AND block_number > $2 \
+            ORDER BY block_number;";
+
+        sqlx::query_as(CHECKPOINTS_AFTER)
+            .bind(chain_id as i64)
+            .bind(block_number as i64)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|(updates,): (String,)| -> Result<AppliedUpdates> {
+                Ok(serde_json::from_str(&updates)?)
+            })
+            .collect()
+    }
+}
+
+#[derive(sqlx::FromRow, Debug)]
+struct OrdersQueryRow {
+    auction_id: i64,
+    encoded_order: Vec<u8>,
+    claimed: bool,
+}
+
+impl OrdersQueryRow {
+    fn into_order(self) -> Result<(u64, Order, bool)> {
+        let bytes: [u8; 32] = self
+            .encoded_order
+            .try_into()
+            .map_err(|_| anyhow!("order has wrong length"))?;
+        Ok((
+            self.auction_id as u64,
+            Order::from_bytes(&bytes),
+            self.claimed,
+        ))
+    }
+}
+
+#[derive(sqlx::FromRow, Debug)]
+struct UsersQueryRow {
+    user_id: i64,
+    user_address: Vec<u8>,
+}
+
+impl UsersQueryRow {
+    fn into_user(self) -> Result<User> {
+        if self.user_address.len() != 20 {
+            return Err(anyhow!("user address has wrong length"));
+        }
+        Ok(User {
+            address: H160::from_slice(&self.user_address),
+            user_id: self.user_id as u64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_reader::{OrderEvent, OrderUpdates};
+    use primitive_types::U256;
+
+    #[tokio::test(flavor = "current_thread")]
+    #[ignore]
+    async fn postgres_orderbook_roundtrip() {
+        let db = Database::new("postgresql://").unwrap();
+        db.clear().await.unwrap();
+        let chain_id = 4u32;
+        assert!(db.load_orderbook(chain_id).await.unwrap().cursor.is_none());
+
+        let order_1 = Order {
+            sell_amount: U256::from_dec_str("2").unwrap(),
+            buy_amount: U256::from_dec_str("2").unwrap(),
+            user_id: 10_u64,
+        };
+        let order_2 = Order {
+            sell_amount: U256::from_dec_str("2").unwrap(),
+            buy_amount: U256::from_dec_str("3").unwrap(),
+            user_id: 10_u64,
+        };
+        let order_3 = Order {
+            sell_amount: U256::from_dec_str("5").unwrap(),
+            buy_amount: U256::from_dec_str("3").unwrap(),
+            user_id: 10_u64,
+        };
+        let user = User {
+            address: "740a98F8f4fAe0986FB3264Fe4aaCf94ac1EE96f".parse().unwrap(),
+            user_id: 10_u64,
+        };
+        let event = |kind, order| OrderEvent {
+            kind,
+            auction_id: 1,
+            order,
+            block_number: 0,
+        };
+        db.persist_updates(
+            chain_id,
+            &[AuctionDetails {
+                auction_id: 1,
+                ..Default::default()
+            }],
+            &AppliedUpdates {
+                from_block: 10,
+                block_hash: H256::from_low_u64_be(12),
+                new_auctions: vec![1],
+                order_updates: OrderUpdates {
+                    order_events: vec![
+                        event(OrderEventKind::Placed, order_1),
+                        event(OrderEventKind::Cancelled, order_1),
+                        event(OrderEventKind::Placed, order_1),
+                        event(OrderEventKind::Placed, order_2),
+                        event(OrderEventKind::Placed, order_3),
+                        event(OrderEventKind::Cancelled, order_2),
+                        event(OrderEventKind::Claimed, order_3),
+                    ],
+                    users_added: vec![user],
+                    last_block_handled: 12,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let persisted = db.load_orderbook(chain_id).await.unwrap();
+        assert_eq!(
+            persisted.cursor,
+            Some(SyncCursor {
+                last_block: 12,
+                block_hash: Some(H256::from_low_u64_be(12)),
+            })
+        );
+        assert_eq!(persisted.auctions.len(), 1);
+        assert_eq!(persisted.auctions[0].auction_id, 1);
+        assert_eq!(persisted.users, vec![user]);
+        let mut orders = persisted.orders;
+        orders.sort_by_key(|(_, order, _)| *order);
+        assert_eq!(orders, vec![(1, order_1, false), (1, order_3, true)]);
+        assert_eq!(persisted.checkpoints.len(), 1);
+        assert_eq!(persisted.checkpoints[0].order_updates.order_events.len(), 7);
+        assert_eq!(
+            db.load_checkpoint_hashes(chain_id).await.unwrap(),
+            vec![(12, H256::from_low_u64_be(12))]
+        );
+        assert!(db.load_orderbook(1).await.unwrap().cursor.is_none());
+
+        // the persisted checkpoints suffice to revert the state, e.g. after a restart
+        let reverted_updates = db.load_checkpoints_after(chain_id, 11).await.unwrap();
+        db.revert_updates(
+            chain_id,
+            &reverted_updates,
+            SyncCursor {
+                last_block: 11,
+                block_hash: None,
+            },
+        )
+        .await
+        .unwrap();
+        let persisted = db.load_orderbook(chain_id).await.unwrap();
+        assert!(persisted.auctions.is_empty());
+        assert!(persisted.orders.is_empty());
+        assert!(persisted.users.is_empty());
+        assert!(persisted.checkpoints.is_empty());
+    }
+}
@@ -8,6 +8,7 @@ use std::convert::TryInto;
 
 #[derive(Default)]
 pub struct SignatureFilter {
+    pub chain_id: u32,
     pub auction_id: u32,
     /// `None` means that this field is unfiltered.
     pub user_address: Option<H160>,
@@ -16,19 +17,21 @@ pub struct SignatureFilter {
 impl Database {
     pub async fn insert_signatures(
         &self,
+        chain_id: u32,
         auction_id: u64,
         users_and_signatures: Vec<SignaturePackage>,
     ) -> Result<(), anyhow::Error> {
         let mut query = String::from(
             "\
             INSERT INTO signatures (
-                auction_id, user_address, signature) \
+                chain_id, auction_id, user_address, signature) \
             VALUES ",
         );
         for item in users_and_signatures {
             //todo: find a better way than this forth and back hex encoding
             query.push_str(&format!(
-                "( {}, decode('{:}', 'hex'),  decode('{}', 'hex')),",
+                "( {}, {}, decode('{:}', 'hex'),  decode('{}', 'hex')),",
+                chain_id,
                 auction_id as u32,
                 hex::encode(item.user.as_bytes()),
                 hex::encode(item.signature.convert_to_bytes()),
@@ -36,7 +39,7 @@ impl Database {
         }
         // removing last comma:
         query = query[..(query.len() - 1)].to_string();
-        query.push_str("ON CONFLICT (auction_id, user_address) DO NOTHING;");
+        query.push_str("ON CONFLICT (chain_id, auction_id, user_address) DO NOTHING;");
         let result = sqlx::query(&query).execute(&self.pool).await;
         match result {
             Ok(_) => Ok(()),
@@ -65,10 +68,12 @@ impl Database {
         FROM \
             signatures s 
         WHERE \
-            s.auction_id = $1 AND \
-            ($2 IS NULL OR s.user_address = $2) 
+            s.chain_id = $1 AND \
+            s.auction_id = $2 AND \
+            ($3 IS NULL OR s.user_address = $3) 
          ";
         sqlx::query_as(QUERY)
+            .bind(filter.chain_id as i64)
             .bind(filter.auction_id)
             .bind(filter.user_address.as_ref().map(|h160| h160.as_bytes()))
             .fetch(&self.pool)
@@ -113,6 +118,7 @@ mod tests {
         let user_address = H160::zero();
         let signature = Signature::default();
         db.insert_signatures(
+            1,
             auction_id as u64,
             vec![SignaturePackage {
                 user: user_address,
@@ -123,6 +129,7 @@ mod tests {
         .unwrap();
         assert!(db
             .insert_signatures(
+                1,
                 auction_id as u64,
                 vec![SignaturePackage {
                     user: user_address,
@@ -132,6 +139,7 @@ mod tests {
             .await
             .is_ok());
         let filter = SignatureFilter {
+            chain_id: 1,
             auction_id,
             user_address: Some(user_address),
         };
@@ -152,6 +160,7 @@ mod tests {
         let auction_id = 2;
         let user_address = H160::zero();
         let filter = SignatureFilter {
+            chain_id: 1,
             auction_id,
             user_address: Some(user_address),
         };
@@ -160,6 +169,7 @@ mod tests {
         let signature = Signature::from_str(&value).unwrap();
 
         db.insert_signatures(
+            1,
             auction_id as u64,
             vec![SignaturePackage {
                 user: user_address,
@@ -183,6 +193,7 @@ mod tests {
         db.clear().await.unwrap();
         let auction_id = 33;
         let filter = SignatureFilter {
+            chain_id: 1,
             auction_id,
             user_address: None,
         };
@@ -201,6 +212,7 @@ mod tests {
         let signature_3 = Signature::from_str(&value).unwrap();
 
         db.insert_signatures(
+            1,
             auction_id as u64,
             vec![
                 SignaturePackage {
@@ -235,6 +247,7 @@ mod tests {
         db.clear().await.unwrap();
         let auction_id = 35;
         let filter = SignatureFilter {
+            chain_id: 1,
             auction_id,
             user_address: None,
         };
@@ -247,6 +260,7 @@ mod tests {
         let value = String::from("0x000000000000000000000000000000000000000000000000000000000000001b172598c8cbf75630449d3edfd4dcddd2eab9e2fc2f854de5f17f58742fa3b55a090a5212d1decfa0c0b43e7466e1b1bb623a3a8ec4ac53adc87b6b905f8676f9");
         let signature_2 = Signature::from_str(&value).unwrap();
         db.insert_signatures(
+            1,
             auction_id as u64,
             vec![
                 SignaturePackage {
@@ -281,6 +295,7 @@ mod tests {
         db.clear().await.unwrap();
         let auction_id = 36;
         let filter = SignatureFilter {
+            chain_id: 1,
             auction_id,
             user_address: None,
         };
@@ -293,6 +308,7 @@ mod tests {
         let value = String::from("0x000000000000000000000000000000000000000000000000000000000000001b172598c8cbf75630449d3edfd4dcddd2eab9e2fc2f854de5f17f58742fa3b55a090a5212d1decfa0c0b43e7466e1b1bb623a3a8ec4ac53adc87b6b905f8676f9");
         let signature_2 = Signature::from_str(&value).unwrap();
         db.insert_signatures(
+            1,
             auction_id as u64,
             vec![SignaturePackage {
                 user: user_address,
@@ -302,6 +318,7 @@ mod tests {
         .await
         .unwrap();
         db.insert_signatures(
+            1,
             auction_id as u64,
             vec![
                 SignaturePackage {
@@ -333,6 +350,7 @@ mod tests {
         db.clear().await.unwrap();
         let auction_id = 3;
         let filter = SignatureFilter {
+            chain_id: 1,
             auction_id,
             user_address: None,
         };
@@ -346,6 +364,7 @@ mod tests {
         let signature_2 = Signature::from_str(&value).unwrap();
 
         db.insert_signatures(
+            1,
             auction_id as u64,
             vec![SignaturePackage {
                 user: user_address,
@@ -355,6 +374,7 @@ mod tests {
         .await
         .unwrap();
         db.insert_signatures(
+            1,
             auction_id as u64,
             vec![SignaturePackage {
                 user: user_address_2,
@@ -392,6 +412,7 @@ mod tests {
             user_id: 10_u64,
         };
         db.insert_signatures(
+            1,
             auction_id,
             vec![SignaturePackage {
                 user: user.address,
@@ -402,6 +423,7 @@ mod tests {
         .unwrap();
         let received_signature = db
             .get_signatures(&SignatureFilter {
+                chain_id: 1,
                 auction_id: (auction_id as u32),
                 user_address: Some(user.address),
             })
@@ -430,6 +452,7 @@ mod tests {
             user_id: 10_u64,
         };
         db.insert_signatures(
+            1,
             auction_id,
             vec![SignaturePackage {
                 user: user.address,
@@ -439,6 +462,7 @@ mod tests {
         .await
         .unwrap();
         db.insert_signatures(
+            1,
             auction_id,
             vec![SignaturePackage {
                 user: user.address,
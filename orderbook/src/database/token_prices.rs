@@ -0,0 +1,81 @@
+use super::*;
+use anyhow::Context;
+use model::decimal::Decimal;
+use primitive_types::H160;
+use std::str::FromStr;
+
+impl Database {
+    /// USD price of the token at the day starting at the given timestamp.
+    pub async fn get_token_price(
+        &self,
+        chain_id: u32,
+        token: H160,
+        day: u64,
+    ) -> Result<Option<Decimal>> {
+        const QUERY: &str = "\
+            SELECT price::text FROM token_prices \
+            WHERE chain_id = $1 AND token = $2 AND day = $3;";
+        let price: Option<(String,)> = sqlx::query_as(QUERY)
+            .bind(chain_id as i64)
+            .bind(token.as_bytes())
+            .bind(day as i64)
+            .fetch_optional(&self.pool)
+            .await
+            .context("get_token_price failed")?;
+        price.map(|(price,)| Decimal::from_str(&price)).transpose()
+    }
+
+    pub async fn insert_token_price(
+        &self,
+        chain_id: u32,
+        token: H160,
+        day: u64,
+        price: Decimal,
+    ) -> Result<()> {
+        const QUERY: &str = "\
+            INSERT INTO token_prices (chain_id, token, day, price) \
+            VALUES ($1, $2, $3, $4::numeric) \
+            ON CONFLICT (chain_id, token, day) DO UPDATE SET price = EXCLUDED.price;";
+        sqlx::query(QUERY)
+            .bind(chain_id as i64)
+            .bind(token.as_bytes())
+            .bind(day as i64)
+            .bind(price.to_string())
+            .execute(&self.pool)
+            .await
+            .context("insert_token_price failed")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "current_thread")]
+    #[ignore]
+    async fn postgres_token_price_roundtrip() {
+        let db = Database::new("postgresql://").unwrap();
+        db.clear().await.unwrap();
+        let token = H160::from_low_u64_be(1);
+        assert_eq!(db.get_token_price(1, token, 86400).await.unwrap(), None);
+
+        let price = Decimal::from_str("1234.000000000000000001").unwrap();
+        db.insert_token_price(1, token, 86400, price).await.unwrap();
+        assert_eq!(
+            db.get_token_price(1, token, 86400).await.unwrap(),
+            Some(price)
+        );
+        assert_eq!(db.get_token_price(4, token, 86400).await.unwrap(), None);
+        assert_eq!(db.get_token_price(1, token, 0).await.unwrap(), None);
+
+        let updated_price = Decimal::from_str("0.5").unwrap();
+        db.insert_token_price(1, token, 86400, updated_price)
+            .await
+            .unwrap();
+        assert_eq!(
+            db.get_token_price(1, token, 86400).await.unwrap(),
+            Some(updated_price)
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! Auctions, whose creation was observed, but whose details could not be read, e.g.
+//! because a call to the node failed. These auctions are quarantined, such that the other
+//! auctions keep being indexed, and retried with an exponential backoff. Likewise, the
+//! clearing of an indexed auction is quarantined, if its volume can not be read, while the
+//! other events of the auction are still applied.
+
+use serde::{Deserialize, Serialize};
+
+/// Delay before the first retry of a quarantined auction in seconds.
+const INITIAL_RETRY_DELAY: u64 = 30;
+/// Upper bound of the retry delay in seconds.
+const MAX_RETRY_DELAY: u64 = 60 * 60;
+
+/// Event of a quarantined auction, which could not be read.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DeadLetterKind {
+    /// The creation of the auction, which is not indexed until its details are read.
+    Creation,
+    /// The clearing of an indexed auction.
+    Clearing,
+}
+
+impl DeadLetterKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeadLetterKind::Creation => "creation",
+            DeadLetterKind::Clearing => "clearing",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "creation" => Some(DeadLetterKind::Creation),
+            "clearing" => Some(DeadLetterKind::Clearing),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetter {
+    pub auction_id: u64,
+    pub kind: DeadLetterKind,
+    /// Block of the event that could not be read.
+    pub block_number: u64,
+    /// Error of the last attempt.
+    pub error: String,
+    pub attempts: u32,
+    /// Unix timestamp, after which the auction is retried.
+    pub next_retry: u64,
+}
+
+impl DeadLetter {
+    pub fn new(
+        auction_id: u64,
+        kind: DeadLetterKind,
+        block_number: u64,
+        error: String,
+        now: u64,
+    ) -> Self {
+        Self {
+            auction_id,
+            kind,
+            block_number,
+            error,
+            attempts: 1,
+            next_retry: now + retry_delay(1),
+        }
+    }
+
+    pub fn record_failure(&mut self, error: String, now: u64) {
+        self.attempts += 1;
+        self.error = error;
+        self.next_retry = now + retry_delay(self.attempts);
+    }
+
+    pub fn is_due(&self, now: u64) -> bool {
+        self.next_retry <= now
+    }
+}
+
+/// Delay after the given number of failed attempts, which doubles with each attempt.
+fn retry_delay(attempts: u32) -> u64 {
+    let doublings = attempts.saturating_sub(1).min(32);
+    (INITIAL_RETRY_DELAY << doublings).min(MAX_RETRY_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_delay_doubles_up_to_the_maximum() {
+        assert_eq!(retry_delay(1), 30);
+        assert_eq!(retry_delay(2), 60);
+        assert_eq!(retry_delay(3), 120);
+        assert_eq!(retry_delay(10), MAX_RETRY_DELAY);
+        assert_eq!(retry_delay(u32::MAX), MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn failures_postpone_the_next_retry() {
+        let mut dead_letter =
+            DeadLetter::new(1, DeadLetterKind::Creation, 10, "timeout".to_string(), 1000);
+        assert!(!dead_letter.is_due(1000));
+        assert!(dead_letter.is_due(1030));
+        dead_letter.record_failure("reverted".to_string(), 1030);
+        assert_eq!(dead_letter.attempts, 2);
+        assert_eq!(dead_letter.error, "reverted");
+        assert!(!dead_letter.is_due(1089));
+        assert!(dead_letter.is_due(1090));
+    }
+}
@@ -1,38 +1,198 @@
-use anyhow::Result;
+use crate::metrics::SyncMetrics;
+use crate::token_metadata::{TokenMetadata, TokenMetadataRegistry};
+use anyhow::{anyhow, Result};
+use contracts::easy_auction::{
+    self,
+    event_data::{AuctionCleared, NewAuction},
+};
 use contracts::EasyAuction;
 use ethabi::ParamType;
 use ethcontract::Address;
-use model::auction_details::AuctionDetails;
+use model::auction_details::{AuctionDetails, AuctionSettlement, AuctionStatus};
+use model::decimal::Decimal;
 use model::order::Order;
-use model::order::OrderWithAuctionId;
 use model::user::User;
 use primitive_types::H160;
+use primitive_types::H256;
 use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::TryInto;
+use std::sync::{Arc, Mutex};
 use tracing::info;
-use web3::types::BlockNumber;
+use web3::types::{BlockId, BlockNumber};
 use web3::Web3;
 
 pub struct EventReader {
     pub contract: EasyAuction,
     pub web3: Web3<web3::transports::Http>,
-    pub number_of_blocks_to_sync_per_request: u64,
+    pub token_metadata: TokenMetadataRegistry,
+    pub metrics: Arc<SyncMetrics>,
+    /// Hashes of the last blocks of the recently processed block ranges.
+    block_hashes: Mutex<BTreeMap<u64, H256>>,
+    blocks_per_request: Mutex<BlocksPerRequest>,
+    /// Partially read histories of quarantined auctions, which are continued by the next
+    /// retry instead of reading them again from the creation of the auction.
+    auction_histories: Mutex<HashMap<u64, (AuctionDetails, OrderUpdates)>>,
 }
 
+/// Lower bound of the number of blocks queried per request.
+const MIN_BLOCKS_PER_REQUEST: u64 = 1;
+/// Upper bound of the number of blocks queried per request.
+const MAX_BLOCKS_PER_REQUEST: u64 = 100_000;
+
+/// Number of blocks queried per request. It is halved whenever the node rejects a range
+/// and grows by a quarter after each successfully queried range.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct BlocksPerRequest(u64);
+
+impl BlocksPerRequest {
+    fn new(blocks_per_request: u64) -> Self {
+        Self(blocks_per_request.clamp(MIN_BLOCKS_PER_REQUEST, MAX_BLOCKS_PER_REQUEST))
+    }
+
+    fn shrink(&mut self) {
+        self.0 = (self.0 / 2).max(MIN_BLOCKS_PER_REQUEST);
+    }
+
+    fn grow(&mut self) {
+        self.0 = (self.0 + (self.0 / 4).max(1)).min(MAX_BLOCKS_PER_REQUEST);
+    }
+}
+
+/// Whether the node rejected a query because of the size of its block range or the number
+/// of its results. Nodes report these limits with different messages, e.g. "query returned
+/// more than 10000 results" or "block range is too wide". Rate limits are not matched, as a
+/// smaller range does not help against them.
+pub fn is_range_limit_error(err: &anyhow::Error) -> bool {
+    let message = format!("{:?}", err).to_lowercase();
+    [
+        "query returned more than",
+        "block range is too wide",
+        "range too large",
+        "response size exceeded",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum OrderEventKind {
+    Placed,
+    Cancelled,
+    Claimed,
+}
+
+/// Placement, cancellation or claim of an order.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OrderEvent {
+    pub kind: OrderEventKind,
+    pub auction_id: u64,
+    pub order: Order,
+    /// Block of the event.
+    pub block_number: u64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct OrderUpdates {
-    pub orders_added: Vec<OrderWithAuctionId>,
-    pub orders_removed: Vec<OrderWithAuctionId>,
-    pub orders_claimed: Vec<OrderWithAuctionId>,
+    /// Order events in the order in which they were emitted on chain.
+    pub order_events: Vec<OrderEvent>,
     pub users_added: Vec<User>,
+    pub auctions_cleared: Vec<AuctionClearing>,
     pub last_block_handled: u64,
 }
 
+impl OrderUpdates {
+    /// Keeps only the updates of the auctions matching the predicate.
+    pub fn retain_auctions(&mut self, keep: impl Fn(u64) -> bool) {
+        self.order_events.retain(|event| keep(event.auction_id));
+        self.auctions_cleared
+            .retain(|clearing| keep(clearing.auction_id));
+    }
+
+    /// Order events of an auction in the order in which they were emitted.
+    pub fn order_events_of_auction(&self, auction_id: u64) -> Vec<OrderEvent> {
+        self.order_events
+            .iter()
+            .filter(|event| event.auction_id == auction_id)
+            .copied()
+            .collect()
+    }
+
+    /// Auctions with order events or a clearing, possibly repeated.
+    pub fn touched_auctions(&self) -> impl Iterator<Item = u64> + '_ {
+        self.order_events
+            .iter()
+            .map(|event| event.auction_id)
+            .chain(
+                self.auctions_cleared
+                    .iter()
+                    .map(|clearing| clearing.auction_id),
+            )
+    }
+
+    /// Appends the updates of the following block range.
+    pub fn append(&mut self, later: OrderUpdates) {
+        self.order_events.extend(later.order_events);
+        self.users_added.extend(later.users_added);
+        self.auctions_cleared.extend(later.auctions_cleared);
+        self.last_block_handled = self.last_block_handled.max(later.last_block_handled);
+    }
+}
+
+/// New auctions of a block range.
+#[derive(Debug, Default)]
+pub struct AuctionUpdates {
+    pub auctions: Vec<AuctionDetails>,
+    pub failed: Vec<FailedAuction>,
+}
+
+/// Auction, whose creation or clearing event was read, but whose details could not be read.
+#[derive(Debug)]
+pub struct FailedAuction {
+    pub auction_id: u64,
+    /// Block of the event.
+    pub block_number: u64,
+    pub error: anyhow::Error,
+}
+
+/// Event of the EasyAuction contract together with its position on chain.
+#[derive(Clone, Debug)]
+pub struct ContractEvent {
+    pub block_number: u64,
+    pub block_hash: H256,
+    pub log_index: usize,
+    pub data: easy_auction::Event,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AuctionClearing {
+    pub auction_id: u64,
+    pub settlement: AuctionSettlement,
+}
+
 pub struct DataFromEvent {
     pub order: Order,
     pub timestamp: u64,
 }
 
-const BLOCK_CONFIRMATION_COUNT: u64 = 15;
+/// Number of the most recently synced block ranges, whose updates are kept in memory for
+/// reverting them after a reorg. Ranges are counted instead of blocks, as one range can span
+/// thousands of blocks. Older ranges are reverted with their persisted checkpoints.
+pub const MAX_CHECKPOINTS: usize = 64;
+/// Number of blocks behind the head of the chain, which are not synced yet, as they are the
+/// most likely ones to be reorganized.
+pub const BLOCK_CONFIRMATION_COUNT: u64 = 15;
+
+/// Result of comparing the recorded block hashes with the canonical chain.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReorgStatus {
+    NoReorg,
+    /// The events of all blocks after the given one were reorganized.
+    RevertAfter(u64),
+    /// None of the recorded blocks is part of the canonical chain anymore.
+    ReorgTooDeep,
+}
 
 impl EventReader {
     pub fn new(
@@ -40,222 +200,550 @@ impl EventReader {
         web3: Web3<web3::transports::Http>,
         number_of_blocks_to_sync_per_request: u64,
     ) -> Self {
+        let blocks_per_request = BlocksPerRequest::new(number_of_blocks_to_sync_per_request);
+        let metrics = Arc::new(SyncMetrics::new());
+        metrics.set_blocks_per_request(blocks_per_request.0);
         Self {
             contract,
+            token_metadata: TokenMetadataRegistry::new(web3.clone(), HashMap::new()),
             web3,
-            number_of_blocks_to_sync_per_request,
+            metrics,
+            block_hashes: Mutex::new(BTreeMap::new()),
+            blocks_per_request: Mutex::new(blocks_per_request),
+            auction_histories: Mutex::new(HashMap::new()),
         }
     }
 
-    pub async fn get_order_updates(&self, from_block: u64, to_block: u64) -> Result<OrderUpdates> {
-        let orders_added = self
-            .get_order_placements_between_blocks(from_block, to_block)
-            .await?;
-        let orders_removed = self
-            .get_cancellations_between_blocks(from_block, to_block)
-            .await?;
-        let orders_claimed = self
-            .get_order_claims_between_blocks(from_block, to_block)
-            .await?;
-        let users_added = self
-            .get_new_users_between_blocks(from_block, to_block)
-            .await?;
-        Ok(OrderUpdates {
-            orders_added,
-            orders_removed,
-            orders_claimed,
-            users_added,
-            last_block_handled: to_block,
-        })
+    /// Number of blocks, whose events are currently queried with one request.
+    pub fn blocks_per_request(&self) -> u64 {
+        self.blocks_per_request.lock().unwrap().0
     }
-    pub async fn get_auction_updates(
+
+    /// Grows the block range of the following requests after a range was queried
+    /// successfully. Shorter ranges, e.g. at the head of the chain, do not show that
+    /// a larger range would be accepted.
+    pub fn record_range_success(&self, from_block: u64, to_block: u64) {
+        let mut blocks_per_request = self.blocks_per_request.lock().unwrap();
+        if to_block - from_block < blocks_per_request.0 {
+            return;
+        }
+        blocks_per_request.grow();
+        self.metrics.set_blocks_per_request(blocks_per_request.0);
+    }
+
+    /// Shrinks the block range of the following requests, if the node rejected the query
+    /// because of its range.
+    pub fn record_range_failure(&self, err: &anyhow::Error) {
+        if !is_range_limit_error(err) {
+            return;
+        }
+        let mut blocks_per_request = self.blocks_per_request.lock().unwrap();
+        blocks_per_request.shrink();
+        tracing::info!(
+            "reducing the block range per request to {} blocks",
+            blocks_per_request.0
+        );
+        self.metrics.set_blocks_per_request(blocks_per_request.0);
+    }
+
+    /// Uses the given token metadata instead of the on-chain metadata of these tokens.
+    pub fn with_token_overrides(mut self, overrides: HashMap<Address, TokenMetadata>) -> Self {
+        self.token_metadata = TokenMetadataRegistry::new(self.web3.clone(), overrides);
+        self
+    }
+
+    /// Returns the hash and the parent hash of the canonical block with the given number.
+    pub async fn get_block_hashes(&self, block_number: u64) -> Result<(H256, H256)> {
+        let block = self
+            .web3
+            .eth()
+            .block(BlockId::Number(BlockNumber::Number(block_number.into())))
+            .await?
+            .ok_or_else(|| anyhow!("block {} is not available", block_number))?;
+        let hash = block
+            .hash
+            .ok_or_else(|| anyhow!("block {} is still pending", block_number))?;
+        Ok((hash, block.parent_hash))
+    }
+
+    pub async fn get_block_timestamp(&self, block_number: u64) -> Result<u64> {
+        let block = self
+            .web3
+            .eth()
+            .block(BlockId::Number(BlockNumber::Number(block_number.into())))
+            .await?
+            .ok_or_else(|| anyhow!("block {} is not available", block_number))?;
+        Ok(block.timestamp.as_u64())
+    }
+
+    /// Timestamps of the given blocks by their number. The blocks are requested concurrently.
+    pub async fn get_block_timestamps(
         &self,
-        from_block: u64,
-        to_block: u64,
-        chain_id: u32,
-    ) -> Result<Vec<AuctionDetails>> {
-        let mut new_auction = Vec::new();
+        block_numbers: impl IntoIterator<Item = u64>,
+    ) -> Result<BTreeMap<u64, u64>> {
+        let block_numbers: Vec<u64> = block_numbers.into_iter().collect();
+        let timestamps = futures::future::try_join_all(
+            block_numbers
+                .iter()
+                .map(|block_number| self.get_block_timestamp(*block_number)),
+        )
+        .await?;
+        Ok(block_numbers.into_iter().zip(timestamps).collect())
+    }
+
+    /// Records the hash of the last block of a synced range. The hashes of the last
+    /// `MAX_CHECKPOINTS` ranges are kept.
+    pub fn record_block_hash(&self, block_number: u64, hash: H256) {
+        let mut block_hashes = self.block_hashes.lock().unwrap();
+        block_hashes.insert(block_number, hash);
+        while block_hashes.len() > MAX_CHECKPOINTS {
+            let first_block = *block_hashes.keys().next().unwrap();
+            block_hashes.remove(&first_block);
+        }
+    }
+
+    /// Forgets the block hashes and the read auction histories after the given block, e.g.
+    /// because the blocks were reorganized.
+    pub fn forget_blocks_after(&self, block_number: u64) {
+        let mut block_hashes = self.block_hashes.lock().unwrap();
+        block_hashes.retain(|&number, _| number <= block_number);
+        let mut auction_histories = self.auction_histories.lock().unwrap();
+        auction_histories.retain(|_, (_, history)| history.last_block_handled <= block_number);
+    }
+
+    /// Checks whether the block with the given number is a child of the recorded block
+    /// before it. Blocks without a recorded predecessor are considered to be children.
+    pub async fn extends_recorded_chain(&self, block_number: u64) -> Result<bool> {
+        let recorded_parent_hash = self
+            .block_hashes
+            .lock()
+            .unwrap()
+            .get(&(block_number - 1))
+            .copied();
+        match recorded_parent_hash {
+            Some(recorded_parent_hash) => {
+                let (_, parent_hash) = self.get_block_hashes(block_number).await?;
+                Ok(parent_hash == recorded_parent_hash)
+            }
+            None => Ok(true),
+        }
+    }
+
+    /// Compares the recorded block hashes, starting with the most recent one, with the
+    /// canonical chain in order to find the last block that was not reorganized.
+    pub async fn check_for_reorg(&self) -> Result<ReorgStatus> {
+        let recorded_block_hashes: Vec<(u64, H256)> = self
+            .block_hashes
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .map(|(number, hash)| (*number, *hash))
+            .collect();
+        if recorded_block_hashes.is_empty() {
+            return Ok(ReorgStatus::NoReorg);
+        }
+        for (index, (block_number, recorded_hash)) in recorded_block_hashes.iter().enumerate() {
+            let (hash, _) = self.get_block_hashes(*block_number).await?;
+            if hash == *recorded_hash {
+                if index == 0 {
+                    return Ok(ReorgStatus::NoReorg);
+                }
+                return Ok(ReorgStatus::RevertAfter(*block_number));
+            }
+        }
+        Ok(ReorgStatus::ReorgTooDeep)
+    }
+
+    /// Reads all events of the EasyAuction contract in the block range with a single
+    /// request, ordered as they were emitted on chain.
+    pub async fn get_events(&self, from_block: u64, to_block: u64) -> Result<Vec<ContractEvent>> {
         let events = self
             .contract
-            .events()
-            .new_auction()
+            .all_events()
             .from_block(BlockNumber::Number(from_block.into()))
             .to_block(BlockNumber::Number(to_block.into()))
             .query()
             .await?;
+        let mut events: Vec<ContractEvent> = events
+            .into_iter()
+            .filter_map(|event| match event.meta {
+                Some(meta) => Some(ContractEvent {
+                    block_number: meta.block_number,
+                    block_hash: meta.block_hash,
+                    log_index: meta.log_index,
+                    data: event.data,
+                }),
+                None => {
+                    tracing::error!("Ignoring event without metadata: {:?}", event.data);
+                    None
+                }
+            })
+            .collect();
+        events.sort_by_key(|event| (event.block_number, event.log_index));
+        Ok(events)
+    }
+    /// Collects the order updates of the events of the block range ending at `to_block`.
+    /// Clearings, whose volume can not be read, are returned as failed instead of failing the
+    /// complete range.
+    pub async fn get_order_updates(
+        &self,
+        events: &[ContractEvent],
+        to_block: u64,
+    ) -> (OrderUpdates, Vec<FailedAuction>) {
+        let mut failed = Vec::new();
+        let mut order_updates = OrderUpdates {
+            last_block_handled: to_block,
+            ..Default::default()
+        };
+        // Bids can only be placed in the block of the settlement by settling atomically, as
+        // regular bids need to be placed before the end of the auction.
+        let mut blocks_with_bids: HashSet<(u64, u64)> = HashSet::new();
         for event in events {
-            let mut event_timestamp: Option<u64> = None;
-            if let Some(event_meta_data) = event.meta.clone() {
-                let block_id = web3::types::BlockId::from(event_meta_data.block_hash);
-                let block_info = self.web3.eth().block(block_id).await?;
-                if let Some(block_data) = block_info {
-                    event_timestamp = Some(block_data.timestamp.as_u64());
-                } else {
-                    tracing::error!("Unable to retrieve auction starting point");
+            let order_event =
+                |kind, auction_id: U256, user_id, buy_amount, sell_amount| OrderEvent {
+                    kind,
+                    auction_id: auction_id.as_u64(),
+                    order: order_from_event(user_id, buy_amount, sell_amount),
+                    block_number: event.block_number,
                 };
-            } else {
-                tracing::error!("Unable to retrieve auction starting point");
-            };
-            let order = Order {
-                sell_amount: U256::from(event.data.auctioned_sell_amount),
-                buy_amount: U256::from(event.data.min_buy_amount),
-                user_id: 0_u64, // todo: set correctly
-            };
-            let address_auctioning_token: Address = event.data.auctioning_token;
-            let address_bidding_token: Address = event.data.bidding_token;
-            let bidding_erc20_contract = contracts::ERC20::at(&self.web3, address_bidding_token);
-            let auctioning_erc20_contract =
-                contracts::ERC20::at(&self.web3, address_auctioning_token);
-            let auction_details_from_rpc_call = self
-                .contract
-                .auction_data(event.data.auction_id)
-                .call()
-                .await?;
-            let is_atomic_closure_allowed = auction_details_from_rpc_call.11;
-            let symbol_auctioning_token = auctioning_erc20_contract.symbol().call().await?;
-            let decimals_auctioning_token =
-                U256::from(auctioning_erc20_contract.decimals().call().await?);
-            let symbol_bidding_token = bidding_erc20_contract.symbol().call().await?;
-            let decimals_bidding_token =
-                U256::from(bidding_erc20_contract.decimals().call().await?);
-            let price_point = order
-                .convert_to_price_point(decimals_bidding_token, decimals_auctioning_token)
-                .invert_price();
-            let mut is_private_auction = true;
-            let allow_list_signer: Address = get_address_from_bytes(event.data.allow_list_data);
-            if event.data.allow_list_contract == H160::from([0u8; 20]) {
-                is_private_auction = false;
+            match &event.data {
+                easy_auction::Event::NewSellOrder(data) => {
+                    blocks_with_bids.insert((data.auction_id.as_u64(), event.block_number));
+                    order_updates.order_events.push(order_event(
+                        OrderEventKind::Placed,
+                        data.auction_id,
+                        data.user_id,
+                        data.buy_amount,
+                        data.sell_amount,
+                    ))
+                }
+                easy_auction::Event::CancellationSellOrder(data) => {
+                    order_updates.order_events.push(order_event(
+                        OrderEventKind::Cancelled,
+                        data.auction_id,
+                        data.user_id,
+                        data.buy_amount,
+                        data.sell_amount,
+                    ))
+                }
+                easy_auction::Event::ClaimedFromOrder(data) => {
+                    order_updates.order_events.push(order_event(
+                        OrderEventKind::Claimed,
+                        data.auction_id,
+                        data.user_id,
+                        data.buy_amount,
+                        data.sell_amount,
+                    ))
+                }
+                easy_auction::Event::NewUser(data) => order_updates.users_added.push(User {
+                    address: data.user_address,
+                    user_id: data.user_id,
+                }),
+                // Users registered directly via `registerUser` only emit a `UserRegistration`
+                // event, whereas users registered via `getUserId` emit it together with
+                // `NewUser`.
+                easy_auction::Event::UserRegistration(data) => {
+                    order_updates.users_added.push(User {
+                        address: data.user,
+                        user_id: data.user_id,
+                    })
+                }
+                easy_auction::Event::AuctionCleared(data) => {
+                    match self
+                        .auction_clearing_from_event(data, event.block_number)
+                        .await
+                    {
+                        Ok(clearing) => order_updates.auctions_cleared.push(clearing),
+                        Err(error) => failed.push(FailedAuction {
+                            auction_id: data.auction_id.as_u64(),
+                            block_number: event.block_number,
+                            error,
+                        }),
+                    }
+                }
+                _ => {}
             }
-            new_auction.push(AuctionDetails {
-                auction_id: event.data.auction_id.as_u64(),
-                order: price_point,
-                exact_order: order,
-                symbol_auctioning_token,
-                symbol_bidding_token,
-                address_bidding_token,
-                address_auctioning_token,
-                decimals_auctioning_token,
-                decimals_bidding_token,
-                minimum_bidding_amount_per_order: event.data.minimum_bidding_amount_per_order,
-                min_funding_threshold: event.data.min_funding_threshold,
-                allow_list_manager: event.data.allow_list_contract,
-                allow_list_signer,
-                order_cancellation_end_date: event.data.order_cancellation_end_date.as_u64(),
-                end_time_timestamp: event.data.auction_end_date.as_u64(),
-                starting_timestamp: event_timestamp.unwrap_or(0_u64),
-                current_clearing_price: price_point.price,
-                current_bidding_amount: U256::zero(),
-                is_private_auction,
-                is_atomic_closure_allowed,
-                chain_id: U256::from(chain_id),
-                interest_score: 0_f64,
-                usd_amount_traded: 0_f64,
-            });
         }
-        Ok(new_auction)
+        for clearing in order_updates.auctions_cleared.iter_mut() {
+            clearing.settlement.is_atomically_closed = blocks_with_bids
+                .contains(&(clearing.auction_id, clearing.settlement.settlement_block));
+        }
+        (order_updates, failed)
     }
-
-    async fn get_order_placements_between_blocks(
+    /// Reads the auctions created by the events. Auctions, whose details can not be read,
+    /// are returned as failed instead of failing the complete range.
+    pub async fn get_auction_updates(
         &self,
-        from_block: u64,
-        to_block: u64,
-    ) -> Result<Vec<OrderWithAuctionId>> {
-        let mut order_updates = Vec::new();
-        let events = self
-            .contract
-            .events()
-            .new_sell_order()
-            .from_block(BlockNumber::Number(from_block.into()))
-            .to_block(BlockNumber::Number(to_block.into()))
-            .query()
-            .await?;
+        events: &[ContractEvent],
+        chain_id: u32,
+    ) -> AuctionUpdates {
+        let mut auction_updates = AuctionUpdates::default();
         for event in events {
-            let order = Order {
-                sell_amount: U256::from(event.data.sell_amount),
-                buy_amount: U256::from(event.data.buy_amount),
-                user_id: event.data.user_id as u64,
-            };
-            let order_update = OrderWithAuctionId {
-                auction_id: event.data.auction_id.as_u64(),
-                order,
-            };
-            order_updates.push(order_update);
+            if let easy_auction::Event::NewAuction(data) = &event.data {
+                match self
+                    .auction_details_from_event(data, event.block_hash, chain_id)
+                    .await
+                {
+                    Ok(details) => auction_updates.auctions.push(details),
+                    Err(error) => auction_updates.failed.push(FailedAuction {
+                        auction_id: data.auction_id.as_u64(),
+                        block_number: event.block_number,
+                        error,
+                    }),
+                }
+            }
         }
-        Ok(order_updates)
+        auction_updates
+    }
+
+    /// Ids of the auctions created by the events together with the blocks of their creation.
+    pub fn auction_creation_blocks(events: &[ContractEvent]) -> Vec<(u64, u64)> {
+        events
+            .iter()
+            .filter_map(|event| match &event.data {
+                easy_auction::Event::NewAuction(data) => {
+                    Some((data.auction_id.as_u64(), event.block_number))
+                }
+                _ => None,
+            })
+            .collect()
     }
 
-    async fn get_order_claims_between_blocks(
+    /// Reads an auction created in the given block together with all its order updates up
+    /// to `to_block`. The part of the history that was read is kept and the next call
+    /// continues after it, until the history is forgotten once its updates are applied.
+    pub async fn get_auction_history(
         &self,
-        from_block: u64,
+        auction_id: u64,
+        block_number: u64,
         to_block: u64,
-    ) -> Result<Vec<OrderWithAuctionId>> {
-        let mut order_updates = Vec::new();
-        let events = self
-            .contract
-            .events()
-            .claimed_from_order()
-            .from_block(BlockNumber::Number(from_block.into()))
-            .to_block(BlockNumber::Number(to_block.into()))
-            .query()
-            .await?;
-        for event in events {
-            let order = Order {
-                sell_amount: U256::from(event.data.sell_amount),
-                buy_amount: U256::from(event.data.buy_amount),
-                user_id: event.data.user_id as u64,
-            };
-            let order_update = OrderWithAuctionId {
-                auction_id: event.data.auction_id.as_u64(),
-                order,
-            };
-            order_updates.push(order_update);
+        chain_id: u32,
+    ) -> Result<(AuctionDetails, OrderUpdates)> {
+        let partial_history = self.auction_histories.lock().unwrap().remove(&auction_id);
+        let (details, mut history) = match partial_history {
+            Some(partial_history) => partial_history,
+            None => {
+                let details = self
+                    .get_auction_details(auction_id, block_number, chain_id)
+                    .await?;
+                let history = OrderUpdates {
+                    last_block_handled: block_number - 1,
+                    ..Default::default()
+                };
+                (details, history)
+            }
+        };
+        let result = self
+            .continue_auction_history(auction_id, to_block, &mut history)
+            .await;
+        self.auction_histories
+            .lock()
+            .unwrap()
+            .insert(auction_id, (details.clone(), history.clone()));
+        result.map(|()| (details, history))
+    }
+
+    /// Forgets the read histories of the given auctions, whose updates were applied.
+    pub fn forget_auction_histories(&self, auction_ids: impl IntoIterator<Item = u64>) {
+        let mut auction_histories = self.auction_histories.lock().unwrap();
+        for auction_id in auction_ids {
+            auction_histories.remove(&auction_id);
         }
-        Ok(order_updates)
     }
 
-    async fn get_new_users_between_blocks(
+    /// Reads the details of an auction created in the given block.
+    async fn get_auction_details(
+        &self,
+        auction_id: u64,
+        block_number: u64,
+        chain_id: u32,
+    ) -> Result<AuctionDetails> {
+        let events = self.get_events(block_number, block_number).await?;
+        let (data, block_hash) = events
+            .iter()
+            .find_map(|event| match &event.data {
+                easy_auction::Event::NewAuction(data) if data.auction_id.as_u64() == auction_id => {
+                    Some((data, event.block_hash))
+                }
+                _ => None,
+            })
+            .ok_or_else(|| {
+                anyhow!(
+                    "auction {} was not created in block {}",
+                    auction_id,
+                    block_number
+                )
+            })?;
+        self.auction_details_from_event(data, block_hash, chain_id)
+            .await
+    }
+
+    /// Adds the order updates of the auction after the last block of the history up to
+    /// `to_block` to the history, one block range after another.
+    async fn continue_auction_history(
         &self,
-        from_block: u64,
+        auction_id: u64,
         to_block: u64,
-    ) -> Result<Vec<User>> {
-        let mut users = Vec::new();
-        let events = self
-            .contract
-            .events()
-            .new_user()
-            .from_block(BlockNumber::Number(from_block.into()))
-            .to_block(BlockNumber::Number(to_block.into()))
-            .query()
-            .await?;
-        for event in events {
-            let user = User {
-                address: event.data.user_address,
-                user_id: event.data.user_id,
+        history: &mut OrderUpdates,
+    ) -> Result<()> {
+        while history.last_block_handled < to_block {
+            let from_block = history.last_block_handled + 1;
+            let chunk_end = to_block.min(from_block + self.blocks_per_request());
+            let events = match self.get_events(from_block, chunk_end).await {
+                Ok(events) => events,
+                Err(err) => {
+                    self.record_range_failure(&err);
+                    return Err(err);
+                }
             };
-            users.push(user);
+            let (mut order_updates, failed) = self.get_order_updates(&events, chunk_end).await;
+            if let Some(failed) = failed
+                .into_iter()
+                .find(|failed| failed.auction_id == auction_id)
+            {
+                return Err(failed.error);
+            }
+            order_updates.retain_auctions(|id| id == auction_id);
+            order_updates.users_added.clear();
+            history.append(order_updates);
         }
-        Ok(users)
+        Ok(())
     }
 
-    pub fn get_to_block(
+    /// Reads the clearing of an auction settled in the given block.
+    pub async fn get_auction_clearing(
         &self,
-        last_handled_block: u64,
-        reorg_protection: bool,
-        current_block: u64,
-    ) -> Result<(u64, u64)> {
-        let mut to_block = current_block;
-        if reorg_protection {
-            to_block -= BLOCK_CONFIRMATION_COUNT;
+        auction_id: u64,
+        block_number: u64,
+    ) -> Result<AuctionClearing> {
+        let events = self.get_events(block_number, block_number).await?;
+        let (order_updates, failed) = self.get_order_updates(&events, block_number).await;
+        if let Some(failed) = failed
+            .into_iter()
+            .find(|failed| failed.auction_id == auction_id)
+        {
+            return Err(failed.error);
+        }
+        order_updates
+            .auctions_cleared
+            .into_iter()
+            .find(|clearing| clearing.auction_id == auction_id)
+            .ok_or_else(|| {
+                anyhow!(
+                    "auction {} was not cleared in block {}",
+                    auction_id,
+                    block_number
+                )
+            })
+    }
+
+    async fn auction_details_from_event(
+        &self,
+        event: &NewAuction,
+        block_hash: H256,
+        chain_id: u32,
+    ) -> Result<AuctionDetails> {
+        let block_id = web3::types::BlockId::from(block_hash);
+        let event_timestamp = match self.web3.eth().block(block_id).await? {
+            Some(block_data) => Some(block_data.timestamp.as_u64()),
+            None => {
+                tracing::error!("Unable to retrieve auction starting point");
+                None
+            }
+        };
+        let order = Order {
+            sell_amount: U256::from(event.auctioned_sell_amount),
+            buy_amount: U256::from(event.min_buy_amount),
+            user_id: 0_u64, // todo: set correctly
+        };
+        let address_auctioning_token: Address = event.auctioning_token;
+        let address_bidding_token: Address = event.bidding_token;
+        let auction_details_from_rpc_call =
+            self.contract.auction_data(event.auction_id).call().await?;
+        let is_atomic_closure_allowed = auction_details_from_rpc_call.11;
+        let auctioning_token = self.token_metadata.get(address_auctioning_token).await?;
+        let bidding_token = self.token_metadata.get(address_bidding_token).await?;
+        let decimals_auctioning_token = U256::from(auctioning_token.decimals);
+        let decimals_bidding_token = U256::from(bidding_token.decimals);
+        // tokens with absurd decimals only miss the displayed price
+        let price_point = order
+            .convert_to_price_point(decimals_bidding_token, decimals_auctioning_token)
+            .map(|price_point| price_point.invert_price())
+            .unwrap_or_default();
+        let mut is_private_auction = true;
+        let allow_list_signer: Address = get_address_from_bytes(event.allow_list_data.clone());
+        if event.allow_list_contract == H160::from([0u8; 20]) {
+            is_private_auction = false;
         }
+        Ok(AuctionDetails {
+            auction_id: event.auction_id.as_u64(),
+            order: price_point,
+            exact_order: order,
+            symbol_auctioning_token: auctioning_token.symbol,
+            symbol_bidding_token: bidding_token.symbol,
+            name_auctioning_token: auctioning_token.name,
+            name_bidding_token: bidding_token.name,
+            logo_uri_auctioning_token: auctioning_token.logo_uri,
+            logo_uri_bidding_token: bidding_token.logo_uri,
+            address_bidding_token,
+            address_auctioning_token,
+            decimals_auctioning_token,
+            decimals_bidding_token,
+            minimum_bidding_amount_per_order: event.minimum_bidding_amount_per_order,
+            min_funding_threshold: event.min_funding_threshold,
+            allow_list_manager: event.allow_list_contract,
+            allow_list_signer,
+            order_cancellation_end_date: event.order_cancellation_end_date.as_u64(),
+            end_time_timestamp: event.auction_end_date.as_u64(),
+            starting_timestamp: event_timestamp.unwrap_or(0_u64),
+            current_clearing_price: price_point.price,
+            exact_current_clearing_price: price_point.exact_price,
+            current_bidding_amount: U256::zero(),
+            is_private_auction,
+            is_atomic_closure_allowed,
+            chain_id: U256::from(chain_id),
+            interest_score: 0_f64,
+            usd_amount_traded: 0_f64,
+            exact_usd_amount_traded: Decimal::zero(),
+            settlement: AuctionSettlement::default(),
+            status: AuctionStatus::default(),
+        })
+    }
+
+    async fn auction_clearing_from_event(
+        &self,
+        event: &AuctionCleared,
+        settlement_block: u64,
+    ) -> Result<AuctionClearing> {
+        // The volume of the clearing order is not part of the event. It is read from the latest
+        // state, as it does not change after the settlement, which does not require an archive
+        // node for old settlements.
+        let auction_details_from_rpc_call =
+            self.contract.auction_data(event.auction_id).call().await?;
+        Ok(AuctionClearing {
+            auction_id: event.auction_id.as_u64(),
+            settlement: AuctionSettlement {
+                is_settled: true,
+                clearing_price_order: Order::from_bytes(&event.clearing_price_order.0),
+                volume_clearing_price_order: U256::from(auction_details_from_rpc_call.9),
+                sold_auctioning_tokens: U256::from(event.sold_auctioning_tokens),
+                sold_bidding_tokens: U256::from(event.sold_bidding_tokens),
+                settlement_block,
+                ..Default::default()
+            },
+        })
+    }
+
+    pub fn get_to_block(&self, last_handled_block: u64, current_block: u64) -> Result<(u64, u64)> {
+        let mut to_block = current_block.saturating_sub(BLOCK_CONFIRMATION_COUNT);
         let from_block = last_handled_block + 1;
         if from_block > to_block {
             anyhow::bail!("Benign interruption: from_block > to_block for updating events")
         }
-        if from_block + self.number_of_blocks_to_sync_per_request < to_block {
-            to_block = std::cmp::min(
-                to_block,
-                from_block + self.number_of_blocks_to_sync_per_request,
-            );
+        let blocks_per_request = self.blocks_per_request();
+        if from_block + blocks_per_request < to_block {
+            to_block = std::cmp::min(to_block, from_block + blocks_per_request);
         }
         info!(
             "Updating event based orderbook from block {} to block {} ",
@@ -263,34 +751,13 @@ impl EventReader {
         );
         Ok((from_block, to_block))
     }
+}
 
-    async fn get_cancellations_between_blocks(
-        &self,
-        from_block: u64,
-        to_block: u64,
-    ) -> Result<Vec<OrderWithAuctionId>> {
-        let mut order_updates = Vec::new();
-        let events = self
-            .contract
-            .events()
-            .cancellation_sell_order()
-            .from_block(BlockNumber::Number(from_block.into()))
-            .to_block(BlockNumber::Number(to_block.into()))
-            .query()
-            .await?;
-        for event in events {
-            let order = Order {
-                sell_amount: U256::from(event.data.sell_amount),
-                buy_amount: U256::from(event.data.buy_amount),
-                user_id: event.data.user_id as u64,
-            };
-            let order_update = OrderWithAuctionId {
-                auction_id: event.data.auction_id.as_u64(),
-                order,
-            };
-            order_updates.push(order_update);
-        }
-        Ok(order_updates)
+fn order_from_event(user_id: u64, buy_amount: u128, sell_amount: u128) -> Order {
+    Order {
+        sell_amount: U256::from(sell_amount),
+        buy_amount: U256::from(buy_amount),
+        user_id,
     }
 }
 
@@ -332,4 +799,48 @@ mod tests {
         assert_eq!(address_from_long, original_address);
         assert_eq!(address_from_short, original_address);
     }
+
+    #[test]
+    fn blocks_per_request_adapts_within_bounds() {
+        let mut blocks_per_request = BlocksPerRequest::new(500);
+        blocks_per_request.grow();
+        assert_eq!(blocks_per_request, BlocksPerRequest(625));
+        blocks_per_request.shrink();
+        assert_eq!(blocks_per_request, BlocksPerRequest(312));
+
+        let mut blocks_per_request = BlocksPerRequest::new(0);
+        assert_eq!(blocks_per_request, BlocksPerRequest(MIN_BLOCKS_PER_REQUEST));
+        blocks_per_request.shrink();
+        assert_eq!(blocks_per_request, BlocksPerRequest(MIN_BLOCKS_PER_REQUEST));
+        blocks_per_request.grow();
+        assert_eq!(blocks_per_request, BlocksPerRequest(2));
+
+        let mut blocks_per_request = BlocksPerRequest::new(MAX_BLOCKS_PER_REQUEST);
+        blocks_per_request.grow();
+        assert_eq!(blocks_per_request, BlocksPerRequest(MAX_BLOCKS_PER_REQUEST));
+    }
+
+    #[test]
+    fn detects_range_limit_errors() {
+        assert!(is_range_limit_error(&anyhow!(
+            "Rpc(Error {{ code: ServerError(-32005), message: \"query returned more than 10000 results\" }})"
+        )));
+        assert!(is_range_limit_error(&anyhow!(
+            "eth_getLogs block range is too wide, max 2000"
+        )));
+        assert!(is_range_limit_error(&anyhow!(
+            "Log response size exceeded. You can make eth_getLogs requests with up to a 2K block range"
+        )));
+        assert!(!is_range_limit_error(&anyhow!("connection refused")));
+        assert!(!is_range_limit_error(&anyhow!(
+            "Transport(\"429 Too Many Requests\")"
+        )));
+        assert!(!is_range_limit_error(&anyhow!(
+            "daily request count exceeded, request rate limited"
+        )));
+        assert!(!is_range_limit_error(&anyhow!("rate limit exceeded")));
+        assert!(!is_range_limit_error(&anyhow!(
+            "invalid block range params"
+        )));
+    }
 }
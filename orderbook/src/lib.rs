@@ -1,28 +1,54 @@
 pub mod api;
+pub mod auction_orders;
 pub mod database;
+pub mod dead_letters;
 pub mod event_reader;
 pub mod health;
+pub mod metrics;
+pub mod network_config;
 pub mod orderbook;
+pub mod price_oracle;
+pub mod search_index;
 pub mod subgraph;
+pub mod token_metadata;
+pub mod update_feed;
 
+use crate::api::AdminToken;
 use crate::database::Database;
 use crate::health::HttpHealthEndpoint;
+use crate::metrics::SyncMetrics;
 use crate::orderbook::Orderbook;
 use std::{net::SocketAddr, sync::Arc};
 use tokio::{task, task::JoinHandle};
 use warp::Filter;
 
+/// Orderbook of a single chain served by this process, together with the health and the
+/// metrics of its synchronization.
+#[derive(Clone)]
+pub struct ChainOrderbook {
+    pub chain_id: u32,
+    pub orderbook: Arc<Orderbook>,
+    pub health: Arc<HttpHealthEndpoint>,
+    pub metrics: Arc<SyncMetrics>,
+}
+
 pub fn serve_task(
-    orderbook: Arc<Orderbook>,
+    chains: Vec<ChainOrderbook>,
     db: Database,
-    health: Arc<HttpHealthEndpoint>,
+    admin_token: Option<AdminToken>,
     address: SocketAddr,
 ) -> JoinHandle<()> {
     let cors = warp::cors()
         .allow_any_origin()
         .allow_methods(vec!["GET", "POST", "DELETE", "OPTIONS", "PUT", "PATCH"])
-        .allow_headers(vec!["Origin", "Content-Type", "X-Auth-Token", "X-AppId"]);
-    let filter = api::handle_all_routes(orderbook, db, health).with(cors);
+        .allow_headers(vec![
+            "Origin",
+            "Content-Type",
+            "X-Auth-Token",
+            "X-AppId",
+            "Authorization",
+        ]);
+    let filter = api::handle_all_routes(chains, db, admin_token).with(cors);
     tracing::debug!(%address, "serving order book");
     task::spawn(warp::serve(filter).bind(address))
 }
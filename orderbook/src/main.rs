@@ -1,18 +1,19 @@
 use contracts::EasyAuction;
-use ethcontract::{Address, H160};
-use lazy_static::lazy_static;
-use maplit::hashmap;
+use orderbook::api::AdminToken;
 use orderbook::database::Database;
-use orderbook::event_reader::EventReader;
+use orderbook::event_reader::{EventReader, BLOCK_CONFIRMATION_COUNT};
 use orderbook::health::HealthReporting;
 use orderbook::health::HttpHealthEndpoint;
+use orderbook::network_config::{load_network_configs, NetworkConfig};
 use orderbook::orderbook::Orderbook;
+use orderbook::price_oracle::{CachedPriceOracle, PriceOracles};
 use orderbook::serve_task;
-use orderbook::subgraph::uniswap_graph_api::UniswapSubgraphClient;
-use primitive_types::H256;
+use orderbook::token_metadata::load_token_list;
+use orderbook::ChainOrderbook;
+use std::collections::HashMap;
 use std::num::ParseFloatError;
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::{collections::HashMap, str::FromStr};
 use std::{net::SocketAddr, time::Duration};
 use structopt::StructOpt;
 use tokio::task;
@@ -34,15 +35,28 @@ struct Arguments {
     #[structopt(long, env = "DB_URL", default_value = "postgresql://")]
     db_url: Url,
 
-    /// The Ethereum node URL to connect to.
+    /// The Ethereum node URLs to connect to, separated by commas. The orderbook of the chain
+    /// of each node is served under `/api/v1/{chain_id}/`.
     #[structopt(
         long,
-        env = "NODE_URL",
-        default_value = "https://rpc.ankr.com/eth"
+        env = "NODE_URLS",
+        default_value = "https://rpc.ankr.com/eth",
+        use_delimiter = true
     )]
-    pub node_url: Url,
+    pub node_urls: Vec<Url>,
 
-    /// Number of blocks to sync in bulk.
+    /// Path to a JSON file configuring the EasyAuction deployment and the tokens used for
+    /// pricing auctions of each chain. Defaults to the officially supported networks.
+    #[structopt(long, env = "NETWORK_CONFIG")]
+    pub network_config: Option<PathBuf>,
+
+    /// Path to a token list in the format of https://tokenlists.org, whose metadata is used
+    /// instead of the on-chain metadata of the listed tokens.
+    #[structopt(long, env = "TOKEN_LIST")]
+    pub token_list: Option<PathBuf>,
+
+    /// Initial number of blocks to sync in bulk. It shrinks, if the node rejects the range,
+    /// and grows again after successful requests.
     #[structopt(
         long,
         env = "NUMBER_OF_BLOCKS_TO_SYNC_PER_REQUEST",
@@ -50,6 +64,12 @@ struct Arguments {
     )]
     pub number_of_blocks_to_sync_per_request: u64,
 
+    /// Token, which authorizes requests to the admin routes, e.g. to
+    /// `/api/v1/{chain_id}/admin/dead_letters`, via an `Authorization: Bearer {token}` header.
+    /// The admin routes are not served without a token.
+    #[structopt(long, env = "ADMIN_TOKEN")]
+    pub admin_token: Option<AdminToken>,
+
     /// Maintance intervall
     #[structopt(
         long,
@@ -60,138 +80,78 @@ struct Arguments {
     pub maintance_interval: Duration,
 }
 
-// Todo: duplication from build file.
-lazy_static! {
-    pub static ref EASY_AUCTION_DEPLOYMENT_INFO: HashMap::<u32, (Address, Option<H256>)> = hashmap! {
-        1 => (Address::from_str("0b7fFc1f4AD541A4Ed16b40D8c37f0929158D101").unwrap(), Some("0xa7ad659a9762720bd86a30b49a3e139928cc2a27d0863ab78110e19d2bef8a51".parse().unwrap())),
-        4 => (Address::from_str("C5992c0e0A3267C7F75493D0F717201E26BE35f7").unwrap(), Some("0xbdd1dde815a908d407ec89fa9bc317d9e33621ccc6452ac0eb00fe2ed0d81ff4".parse().unwrap())),
-        5 => (Address::from_str("1fbab40c338e2e7243da945820ba680c92ef8281").unwrap(), Some("0x6cbf82cec76ea4800d51150478fce1fbfb2284e450624489fbe3dbd4324fcc4b".parse().unwrap())),
-        100 => (Address::from_str("0b7fFc1f4AD541A4Ed16b40D8c37f0929158D101").unwrap(), Some("0x5af5443ba9add113a42b0219ac8f398c383dc5a3684a221fd24c5655b8316931".parse().unwrap())),
-        137 => (Address::from_str("0b7fFc1f4AD541A4Ed16b40D8c37f0929158D101").unwrap(), Some("0x6093f70c46350202181e9b0edfcf8f0e966ddddeb8b24e8b73dd2ab636c1ce87".parse().unwrap())),
-        43114 => (Address::from_str("0xb5D00F83680ea5E078e911995c64b43Fbfd1eE61").unwrap(), Some("0xa6fa39783a488c892f28ce75ec2d8d079fb8d7ac4647c09bed9755e4246fd390".parse().unwrap())),
-    };
-}
-
 pub async fn orderbook_maintenance(
-    orderbook_latest: Arc<Orderbook>,
-    orderbook_reorg_protected: Arc<Orderbook>,
+    orderbook: Arc<Orderbook>,
     event_reader: EventReader,
-    mut the_graph_reader: UniswapSubgraphClient,
+    network: NetworkConfig,
+    price_oracle: CachedPriceOracle<PriceOracles>,
     health: Arc<HttpHealthEndpoint>,
     maintance_interval: Duration,
+    database: Database,
 ) -> ! {
-    // First block considered for synchronization should be the one, in which the deployment
-    // of Gnosis Auction contract happens
     let chain_id = event_reader.web3.eth().chain_id().await.unwrap();
-    let tx_info = event_reader
-        .web3
-        .eth()
-        .transaction(
-            EASY_AUCTION_DEPLOYMENT_INFO
-                .clone()
-                .get(&chain_id.as_u32())
-                .unwrap_or(&(Address::zero(), None))
-                .1
-                .unwrap()
-                .into(),
-        )
+    let deployment_block = get_deployment_block(&event_reader, &network).await;
+    let persisted_cursor = orderbook
+        .load_from_database(&database, chain_id.as_u32())
         .await
-        .unwrap();
-    let mut last_block_considered_for_reorg_protected_orderbook = match tx_info {
-        Some(tx) => tx.block_number.unwrap().as_u64(),
-        None => {
-            tracing::error!("Deployment block was not found");
-            0u64
+        .expect("loading the persisted orderbook failed");
+    let mut last_block_considered = match persisted_cursor {
+        Some(cursor) => {
+            tracing::info!(
+                "Continuing synchronization from persisted block {}",
+                cursor.last_block
+            );
+            // Blocks, which were reorganized while the service was down, are detected by the
+            // first reorg check and reverted with the persisted checkpoints.
+            for updates in orderbook.applied_updates.read().await.iter() {
+                event_reader.record_block_hash(
+                    updates.order_updates.last_block_handled,
+                    updates.block_hash,
+                );
+            }
+            if let Some(block_hash) = cursor.block_hash {
+                event_reader.record_block_hash(cursor.last_block, block_hash);
+            }
+            cursor.last_block
         }
+        None => deployment_block,
     };
 
     let mut fully_indexed_events = false;
-    let mut current_block = event_reader
-        .web3
-        .eth()
-        .block_number()
-        .await
-        .unwrap_or_else(|_| web3::types::U64::zero())
-        .as_u64();
     loop {
-        tracing::debug!("running order book maintenance with reorg protection");
-        orderbook_reorg_protected
-            .run_maintenance(
+        tracing::debug!("running order book maintenance for chain {}", chain_id);
+        if let Err(err) = orderbook
+            .handle_reorg(
                 &event_reader,
-                &mut the_graph_reader,
-                &mut last_block_considered_for_reorg_protected_orderbook,
-                true,
+                &mut last_block_considered,
+                deployment_block,
                 chain_id.as_u32(),
-                current_block,
+                Some(&database),
             )
             .await
-            .expect("maintenance function not successful");
-
-        let mut last_block_considered = last_block_considered_for_reorg_protected_orderbook; // Values are cloned, as we don't wanna store the values.
-
-        {
-            let mut orderbook = orderbook_latest.orders.write().await;
-            let orderbook_reorg_save = orderbook_reorg_protected.orders.read().await;
-            *orderbook = orderbook_reorg_save.clone();
-        }
-        {
-            let mut orderbook = orderbook_latest.orders_display.write().await;
-            let orderbook_reorg_save = orderbook_reorg_protected.orders_display.read().await;
-            *orderbook = orderbook_reorg_save.clone();
-        }
-        {
-            let mut orderbook = orderbook_latest.orders_without_claimed.write().await;
-            let orderbook_reorg_save = orderbook_reorg_protected
-                .orders_without_claimed
-                .read()
-                .await;
-            *orderbook = orderbook_reorg_save.clone();
-        }
-        {
-            let mut orderbook = orderbook_latest.auction_details.write().await;
-            let orderbook_reorg_save = orderbook_reorg_protected.auction_details.read().await;
-            *orderbook = orderbook_reorg_save.clone();
-        }
-        {
-            let mut users = orderbook_latest.users.write().await;
-            let users_reorg_save = orderbook_reorg_protected.users.read().await;
-            users.retain(|&k, _| k == H160::zero());
-            for address in users_reorg_save.keys() {
-                users.insert(*address, *users_reorg_save.get(address).unwrap());
-            }
-        }
         {
-            let mut auction_participation = orderbook_latest.auction_participation.write().await;
-            let auction_participation_reorg_save =
-                orderbook_reorg_protected.auction_participation.read().await;
-            *auction_participation = auction_participation_reorg_save.clone();
+            tracing::info!("handle_reorg was not successful with error: {:}", err);
         }
-        // Only look forward without reorg protection, in case the sync process is close to the top of the chain.
-        current_block = event_reader
+        let current_block = event_reader
             .web3
             .eth()
             .block_number()
             .await
             .unwrap_or_else(|_| web3::types::U64::zero())
             .as_u64();
-        if last_block_considered_for_reorg_protected_orderbook
-            + 2 * event_reader.number_of_blocks_to_sync_per_request
-            > current_block
-        {
-            orderbook_latest
-                .run_maintenance(
-                    &event_reader,
-                    &mut the_graph_reader,
-                    &mut last_block_considered,
-                    false,
-                    chain_id.as_u32(),
-                    current_block,
-                )
-                .await
-                .expect("maintenance function not successful");
-        }
+        orderbook
+            .run_maintenance(
+                &event_reader,
+                &price_oracle,
+                &mut last_block_considered,
+                chain_id.as_u32(),
+                current_block,
+                Some(&database),
+            )
+            .await
+            .expect("maintenance function not successful");
 
-        if current_block == last_block_considered {
+        if last_block_considered + BLOCK_CONFIRMATION_COUNT >= current_block {
             health.notify_ready();
             fully_indexed_events = true;
             tracing::debug!("Orderbook fully synced");
@@ -202,44 +162,100 @@ pub async fn orderbook_maintenance(
     }
 }
 
+// First block considered for synchronization should be the one, in which the deployment
+// of Gnosis Auction contract happens
+async fn get_deployment_block(event_reader: &EventReader, network: &NetworkConfig) -> u64 {
+    if let Some(start_block) = network.start_block {
+        return start_block;
+    }
+    let tx_info = match network.deployment_transaction {
+        Some(deployment_transaction) => event_reader
+            .web3
+            .eth()
+            .transaction(deployment_transaction.into())
+            .await
+            .unwrap(),
+        None => None,
+    };
+    match tx_info {
+        Some(tx) => tx.block_number.unwrap().as_u64(),
+        None => {
+            tracing::error!("Deployment block was not found");
+            0u64
+        }
+    }
+}
+
 #[tokio::main(flavor = "multi_thread", worker_threads = 4)]
 async fn main() {
     let args = Arguments::from_args();
     tracing_setup::initialize(args.log_filter.as_str());
     tracing::debug!("running order book with {:#?}", args);
-    let transport =
-        web3::transports::Http::new(args.node_url.as_str()).expect("transport creation failed");
-    let web3 = web3::Web3::new(transport);
-    let easy_auction_contract = EasyAuction::deployed(&web3)
-        .await
-        .expect("Couldn't load deployed easyAuction");
-    let event_reader = EventReader::new(
-        easy_auction_contract,
-        web3,
-        args.number_of_blocks_to_sync_per_request,
-    );
     let database = Database::new(args.db_url.as_str()).expect("failed to create database");
-    let orderbook_latest = Arc::new(Orderbook::new());
-    let orderbook_reorg_save = Arc::new(Orderbook::new());
-    let the_graph_reader = UniswapSubgraphClient::for_chain(1).unwrap();
-    let health = Arc::new(HttpHealthEndpoint::new());
-    let serve_task = serve_task(
-        orderbook_latest.clone(),
-        database,
-        health.clone(),
-        args.bind_address,
-    );
-    let maintenance_task = task::spawn(orderbook_maintenance(
-        orderbook_latest,
-        orderbook_reorg_save,
-        event_reader,
-        the_graph_reader,
-        health,
-        args.maintance_interval,
-    ));
+    let network_configs = load_network_configs(args.network_config.as_deref())
+        .expect("loading the network config failed");
+    let mut chains: Vec<ChainOrderbook> = Vec::new();
+    let mut maintenance_tasks = Vec::new();
+    for node_url in &args.node_urls {
+        let transport =
+            web3::transports::Http::new(node_url.as_str()).expect("transport creation failed");
+        let web3 = web3::Web3::new(transport);
+        let chain_id = web3
+            .eth()
+            .chain_id()
+            .await
+            .expect("Couldn't load chain id")
+            .as_u32();
+        assert!(
+            chains.iter().all(|chain| chain.chain_id != chain_id),
+            "chain id {} is served by several nodes",
+            chain_id
+        );
+        let network = network_configs
+            .get(&chain_id)
+            .unwrap_or_else(|| panic!("chain id {} is not configured", chain_id))
+            .clone();
+        let easy_auction_contract = EasyAuction::at(&web3, network.easy_auction);
+        let price_oracle = CachedPriceOracle::new(
+            PriceOracles::from_configs(&network.price_oracles, &web3)
+                .expect("creating the price oracles failed"),
+            database.clone(),
+            chain_id,
+        );
+        let token_overrides = match &args.token_list {
+            Some(path) => load_token_list(path, chain_id).expect("loading the token list failed"),
+            None => HashMap::new(),
+        };
+        let event_reader = EventReader::new(
+            easy_auction_contract,
+            web3,
+            args.number_of_blocks_to_sync_per_request,
+        )
+        .with_token_overrides(token_overrides);
+        let orderbook = Arc::new(Orderbook::for_network(network.clone()));
+        let health = Arc::new(HttpHealthEndpoint::new());
+        chains.push(ChainOrderbook {
+            chain_id,
+            orderbook: orderbook.clone(),
+            health: health.clone(),
+            metrics: event_reader.metrics.clone(),
+        });
+        maintenance_tasks.push(task::spawn(orderbook_maintenance(
+            orderbook,
+            event_reader,
+            network,
+            price_oracle,
+            health,
+            args.maintance_interval,
+            database.clone(),
+        )));
+    }
+    let serve_task = serve_task(chains, database, args.admin_token, args.bind_address);
     tokio::select! {
         result = serve_task => tracing::error!(?result, "serve task exited"),
-        result = maintenance_task => tracing::error!(?result, "maintenance task exited"),
+        (result, _, _) = futures::future::select_all(maintenance_tasks) => {
+            tracing::error!(?result, "maintenance task exited")
+        }
     };
 }
 
@@ -0,0 +1,66 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Metrics of the synchronization of the orderbook of a chain, which are served in the
+/// Prometheus text format.
+#[derive(Debug, Default)]
+pub struct SyncMetrics {
+    blocks_per_request: AtomicU64,
+}
+
+impl SyncMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of blocks, whose events are queried with one request.
+    pub fn blocks_per_request(&self) -> u64 {
+        self.blocks_per_request.load(Ordering::SeqCst)
+    }
+
+    pub fn set_blocks_per_request(&self, blocks_per_request: u64) {
+        self.blocks_per_request
+            .store(blocks_per_request, Ordering::SeqCst);
+    }
+}
+
+/// Encodes the metrics of the given chains in the Prometheus text format.
+pub fn encode(metrics: &[(u32, &SyncMetrics)]) -> String {
+    let mut text = String::new();
+    writeln!(
+        text,
+        "# HELP orderbook_blocks_per_request Number of blocks queried per eth_getLogs request."
+    )
+    .unwrap();
+    writeln!(text, "# TYPE orderbook_blocks_per_request gauge").unwrap();
+    for (chain_id, metrics) in metrics {
+        writeln!(
+            text,
+            "orderbook_blocks_per_request{{chain_id=\"{}\"}} {}",
+            chain_id,
+            metrics.blocks_per_request()
+        )
+        .unwrap();
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_gauge_per_chain() {
+        let mainnet = SyncMetrics::new();
+        mainnet.set_blocks_per_request(500);
+        let xdai = SyncMetrics::new();
+        xdai.set_blocks_per_request(125);
+        assert_eq!(
+            encode(&[(1, &mainnet), (100, &xdai)]),
+            "# HELP orderbook_blocks_per_request Number of blocks queried per eth_getLogs request.\n\
+             # TYPE orderbook_blocks_per_request gauge\n\
+             orderbook_blocks_per_request{chain_id=\"1\"} 500\n\
+             orderbook_blocks_per_request{chain_id=\"100\"} 125\n"
+        );
+    }
+}
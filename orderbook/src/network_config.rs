@@ -0,0 +1,95 @@
+//! Configuration of the networks the service can index. The configuration is read at
+//! runtime, so that custom or forked networks can be added without recompiling.
+
+use crate::price_oracle::PriceOracleConfig;
+use anyhow::{Context, Result};
+use ethcontract::Address;
+use primitive_types::H256;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Configuration of the officially supported networks, which is used if no configuration
+/// file is provided.
+const DEFAULT_NETWORK_CONFIG: &str = include_str!("../networks.json");
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkConfig {
+    pub easy_auction: Address,
+    /// Transaction deploying the EasyAuction contract. Its block is used as start block, if
+    /// none is configured.
+    #[serde(default)]
+    pub deployment_transaction: Option<H256>,
+    /// First block, whose events are indexed.
+    #[serde(default)]
+    pub start_block: Option<u64>,
+    /// Tokens, whose amounts are considered as USD amounts.
+    #[serde(default)]
+    pub stable_coins: Vec<Address>,
+    /// Oracles pricing the bidding tokens, which are not stable coins.
+    #[serde(default)]
+    pub price_oracles: Vec<PriceOracleConfig>,
+}
+
+/// Loads the network configurations by chain id from the given JSON file, or the default
+/// configuration if no file is given.
+pub fn load_network_configs(path: Option<&Path>) -> Result<HashMap<u32, NetworkConfig>> {
+    match path {
+        Some(path) => {
+            let config = fs::read_to_string(path)
+                .with_context(|| format!("reading network config {} failed", path.display()))?;
+            parse_network_configs(&config)
+                .with_context(|| format!("parsing network config {} failed", path.display()))
+        }
+        None => parse_network_configs(DEFAULT_NETWORK_CONFIG),
+    }
+}
+
+pub fn parse_network_configs(config: &str) -> Result<HashMap<u32, NetworkConfig>> {
+    Ok(serde_json::from_str(config)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    #[test]
+    fn default_config_contains_supported_networks() {
+        let configs = load_network_configs(None).unwrap();
+        for chain_id in &[1, 4, 5, 100, 137, 43114] {
+            assert!(configs[chain_id].deployment_transaction.is_some());
+        }
+        assert_eq!(
+            configs[&1].easy_auction,
+            Address::from_str("0b7fFc1f4AD541A4Ed16b40D8c37f0929158D101").unwrap()
+        );
+        assert_eq!(configs[&1].stable_coins.len(), 3);
+        assert_eq!(configs[&1].price_oracles.len(), 1);
+        assert!(configs[&100].price_oracles.is_empty());
+    }
+
+    #[test]
+    fn parses_custom_network() {
+        let config = json!({
+            "31337": {
+                "easyAuction": "0x5fbdb2315678afecb367f032d93f642f64180aa3",
+                "startBlock": 12
+            }
+        });
+        let configs = parse_network_configs(&config.to_string()).unwrap();
+        assert_eq!(
+            configs[&31337],
+            NetworkConfig {
+                easy_auction: "0x5fbdb2315678afecb367f032d93f642f64180aa3"
+                    .parse()
+                    .unwrap(),
+                start_block: Some(12),
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -1,41 +1,105 @@
-use crate::event_reader::EventReader;
-use crate::subgraph::uniswap_graph_api::UniswapSubgraphClient;
+use crate::auction_orders::AuctionOrders;
+use crate::database::{Database, SyncCursor};
+use crate::dead_letters::{DeadLetter, DeadLetterKind};
+use crate::event_reader::{
+    EventReader, OrderEvent, OrderEventKind, OrderUpdates, ReorgStatus, MAX_CHECKPOINTS,
+};
+use crate::network_config::NetworkConfig;
+use crate::price_oracle::{is_day_over, PriceOracle};
+use crate::search_index::SearchIndex;
+use crate::update_feed::{AuctionEvent, AuctionUpdate, UpdateFeed};
 use anyhow::{anyhow, Result};
 use ethcontract::Address;
 use ethcontract::H160;
 use lazy_static::lazy_static;
-use maplit::hashmap;
-use model::auction_details::AuctionDetails;
-use model::order::TEN;
-use model::order::{Order, OrderWithAuctionId, OrderbookDisplay, PricePoint};
+use model::auction_details::{
+    AuctionDetails, AuctionListQuery, AuctionSettlement, AuctionStatus, ClearingPricePoint,
+};
+use model::decimal::Decimal;
+use model::depth_chart::{DepthChart, DepthPoint};
+use model::order::{BidSimulation, Order, OrderSimulation, OrderbookDisplay, PricePoint};
+use model::stats::{PlatformStats, StatsFilter, TokenStats};
 use model::user::User;
-use primitive_types::U256;
-use std::collections::{hash_map::Entry, HashMap, HashSet};
-use std::str::FromStr;
+use primitive_types::{H256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet};
 use std::time::SystemTime;
 use tokio::sync::RwLock;
 
 #[derive(Default, Debug)]
 pub struct Orderbook {
-    pub orders: RwLock<HashMap<u64, Vec<Order>>>,
-    pub orders_display: RwLock<HashMap<u64, Vec<PricePoint>>>,
-    pub orders_without_claimed: RwLock<HashMap<u64, Vec<Order>>>,
+    pub orders: RwLock<HashMap<u64, AuctionOrders>>,
     pub users: RwLock<HashMap<Address, u64>>,
+    pub user_addresses: RwLock<HashMap<u64, Address>>,
     pub auction_participation: RwLock<HashMap<u64, HashSet<u64>>>,
     pub auction_details: RwLock<HashMap<u64, AuctionDetails>>,
+    pub applied_updates: RwLock<Vec<AppliedUpdates>>,
+    pub update_feed: UpdateFeed,
+    pub network: NetworkConfig,
+    pub search_index: RwLock<SearchIndex>,
+    /// Quarantined auctions by their id.
+    pub dead_letters: RwLock<BTreeMap<u64, DeadLetter>>,
+    /// Auctions, whose clearing price info is recomputed in the next maintenance run even
+    /// without new events, as it was reverted, loaded or not yet valued for good.
+    pub stale_auctions: RwLock<HashSet<u64>>,
+    /// Timestamp of the last synced block, at which the status of the auctions is computed.
+    /// 0 as long as no block range was synced.
+    pub block_timestamp: RwLock<u64>,
 }
-lazy_static! {
-    pub static ref LEGIT_STABLE_COINS: HashMap::<u32, Vec<Address>> = hashmap! {
-        1 => vec![Address::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap(),Address::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap(), Address::from_str("0xdac17f958d2ee523a2206206994597c13d831ec7").unwrap()],
-        4 => vec![Address::from_str("0x5592EC0cfb4dbc12D3aB100b257153436a1f0FEa").unwrap(),Address::from_str("0x4DBCdF9B62e891a7cec5A2568C3F4FAF9E8Abe2b").unwrap()],
-        5 => vec![],
-        100 => vec![Address::from_str("0xe91D153E0b41518A2Ce8Dd3D7944Fa863463a97d").unwrap()],
-        137 => vec![Address::from_str("0x8f3Cf7ad23Cd3CaDbD9735AFf958023239c6A063").unwrap(), Address::from_str("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174").unwrap()],
-        43114 => vec![Address::from_str("0xA7D7079b0FEaD91F3e65f86E8915Cb59c1a4C664").unwrap(), Address::from_str("0xB97EF9Ef8734C71904D8002F8b6Bc66Dd9c48a6E").unwrap()],
-    };
-    pub static ref PRICE_FEED_SUPPORTED_TOKENS: HashMap::<u32, Vec<Address>> = hashmap! {
-     1 => vec![Address::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap()],
-    };
+
+/// State of an order in the orderbook of its auction. Order events move an order between
+/// these states, any other event indicates an inconsistent orderbook.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OrderState {
+    /// Never placed or cancelled.
+    Absent,
+    Open,
+    Claimed,
+}
+
+impl OrderState {
+    /// States of an order before and after an event.
+    fn transition(kind: OrderEventKind) -> (OrderState, OrderState) {
+        match kind {
+            OrderEventKind::Placed => (OrderState::Absent, OrderState::Open),
+            OrderEventKind::Cancelled => (OrderState::Open, OrderState::Absent),
+            OrderEventKind::Claimed => (OrderState::Open, OrderState::Claimed),
+        }
+    }
+
+    /// Returns the state after the event or `None`, if the event is impossible in this state.
+    pub fn apply(self, kind: OrderEventKind) -> Option<OrderState> {
+        let (before, after) = Self::transition(kind);
+        if self == before {
+            Some(after)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the state before the event or `None`, if the event can not have led to this
+    /// state.
+    pub fn revert(self, kind: OrderEventKind) -> Option<OrderState> {
+        let (before, after) = Self::transition(kind);
+        if self == after {
+            Some(before)
+        } else {
+            None
+        }
+    }
+}
+
+/// Updates of a synced block range, kept for reverting them in case of a reorg.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AppliedUpdates {
+    pub from_block: u64,
+    /// Hash of the last block of the range, which is `order_updates.last_block_handled`.
+    pub block_hash: H256,
+    pub new_auctions: Vec<u64>,
+    pub order_updates: OrderUpdates,
+    /// Quarantined auctions among the new auctions, which are quarantined again if the
+    /// updates are reverted.
+    pub recovered_auctions: Vec<DeadLetter>,
 }
 
 lazy_static! {
@@ -45,16 +109,46 @@ lazy_static! {
         user_id: 0_u64,
     };
 }
+
+/// Number of bidding tokens listed in the platform statistics.
+const NUMBER_OF_TOP_BIDDING_TOKENS: usize = 10;
+const MAX_SEARCH_RESULTS: usize = 100;
+
+/// Order of the auction statuses in search results, where lower ranks come first.
+fn search_rank(status: AuctionStatus) -> u8 {
+    match status {
+        AuctionStatus::OpenWithCancellation => 0,
+        AuctionStatus::OpenNoCancellation => 1,
+        AuctionStatus::Created => 2,
+        AuctionStatus::Ended => 3,
+        AuctionStatus::Settled
+        | AuctionStatus::FundingThresholdNotReached
+        | AuctionStatus::AtomicallyClosed => 4,
+    }
+}
+
 impl Orderbook {
     #[allow(dead_code)]
     pub fn new() -> Self {
         Orderbook {
             orders: RwLock::new(HashMap::new()),
-            orders_display: RwLock::new(HashMap::new()),
-            orders_without_claimed: RwLock::new(HashMap::new()),
             users: RwLock::new(HashMap::new()),
+            user_addresses: RwLock::new(HashMap::new()),
             auction_participation: RwLock::new(HashMap::new()),
             auction_details: RwLock::new(HashMap::new()),
+            applied_updates: RwLock::new(Vec::new()),
+            update_feed: UpdateFeed::default(),
+            network: NetworkConfig::default(),
+            search_index: RwLock::new(SearchIndex::default()),
+            dead_letters: RwLock::new(BTreeMap::new()),
+            stale_auctions: RwLock::new(HashSet::new()),
+            block_timestamp: RwLock::new(0),
+        }
+    }
+    pub fn for_network(network: NetworkConfig) -> Self {
+        Orderbook {
+            network,
+            ..Orderbook::new()
         }
     }
     pub async fn insert_orders(&self, auction_id: u64, orders: Vec<Order>) {
@@ -63,45 +157,9 @@ impl Orderbook {
         }
         {
             let mut hashmap = self.orders.write().await;
-            match hashmap.entry(auction_id) {
-                Entry::Occupied(mut order_vec) => {
-                    order_vec.get_mut().extend(orders.clone());
-                }
-                Entry::Vacant(_) => {
-                    hashmap.insert(auction_id, orders.clone());
-                }
-            }
-        }
-        {
-            let mut hashmap = self.orders_without_claimed.write().await;
-            match hashmap.entry(auction_id) {
-                Entry::Occupied(mut order_vec) => {
-                    order_vec.get_mut().extend(orders.clone());
-                }
-                Entry::Vacant(_) => {
-                    hashmap.insert(auction_id, orders.clone());
-                }
-            }
-        }
-        let (decimals_auctioning_token, decimals_bidding_token) =
-            self.get_decimals(auction_id).await.unwrap_or_else(|_| {
-                panic!("get_decimals returns error for auction_id {:}", auction_id)
-            });
-        {
-            let mut hashmap = self.orders_display.write().await;
-            let vec_price_points: Vec<PricePoint> = orders
-                .iter()
-                .map(|order| {
-                    order.convert_to_price_point(decimals_auctioning_token, decimals_bidding_token)
-                })
-                .collect();
-            match hashmap.entry(auction_id) {
-                Entry::Occupied(mut order_vec) => {
-                    order_vec.get_mut().extend(vec_price_points);
-                }
-                Entry::Vacant(_) => {
-                    hashmap.insert(auction_id, vec_price_points);
-                }
+            let auction_orders = hashmap.entry(auction_id).or_default();
+            for order in &orders {
+                auction_orders.insert(*order);
             }
         }
         {
@@ -131,8 +189,10 @@ impl Orderbook {
             return;
         }
         let mut hashmap = self.users.write().await;
+        let mut reverse_hashmap = self.user_addresses.write().await;
         for user in users {
             hashmap.insert(user.address, user.user_id);
+            reverse_hashmap.insert(user.user_id, user.address);
         }
     }
     pub async fn update_initial_order(&mut self, auction_id: u64, order: Order) {
@@ -153,33 +213,6 @@ impl Orderbook {
             }
         }
     }
-    pub async fn sort_orders(&self, auction_id: u64) {
-        let mut hashmap = self.orders.write().await;
-        match hashmap.entry(auction_id) {
-            Entry::Occupied(order_vec) => {
-                order_vec.into_mut().sort();
-            }
-            Entry::Vacant(_) => {}
-        }
-    }
-    pub async fn sort_orders_display(&self, auction_id: u64) {
-        let mut hashmap = self.orders_display.write().await;
-        match hashmap.entry(auction_id) {
-            Entry::Occupied(order_vec) => {
-                order_vec.into_mut().sort();
-            }
-            Entry::Vacant(_) => {}
-        }
-    }
-    pub async fn sort_orders_without_claimed(&self, auction_id: u64) {
-        let mut hashmap = self.orders_without_claimed.write().await;
-        match hashmap.entry(auction_id) {
-            Entry::Occupied(order_vec) => {
-                order_vec.into_mut().sort();
-            }
-            Entry::Vacant(_) => {}
-        }
-    }
     pub async fn get_decimals(&self, auction_id: u64) -> Result<(U256, U256)> {
         let decimals_auctioning_token;
         let decimals_bidding_token;
@@ -208,46 +241,9 @@ impl Orderbook {
         Ok((decimals_auctioning_token, decimals_bidding_token))
     }
     pub async fn remove_orders(&self, auction_id: u64, orders: Vec<Order>) {
-        if orders.is_empty() {
-            return;
-        }
-        {
-            let mut hashmap = self.orders.write().await;
-            match hashmap.entry(auction_id) {
-                Entry::Occupied(order_vec) => {
-                    order_vec.into_mut().retain(|x| !orders.contains(x));
-                }
-                Entry::Vacant(_) => (),
-            }
-        }
-        let (decimals_auctioning_token, decimals_bidding_token) =
-            self.get_decimals(auction_id).await.unwrap_or_else(|_| {
-                panic!("get_decimals returns error for auction_id {:}", auction_id)
-            });
-        {
-            let mut hashmap = self.orders_display.write().await;
-            let vec_price_points: Vec<PricePoint> = orders
-                .iter()
-                .map(|order| {
-                    order.convert_to_price_point(decimals_auctioning_token, decimals_bidding_token)
-                })
-                .collect();
-            match hashmap.entry(auction_id) {
-                Entry::Occupied(order_vec) => {
-                    order_vec
-                        .into_mut()
-                        .retain(|x| !vec_price_points.contains(x));
-                }
-                Entry::Vacant(_) => (),
-            }
-        }
-        {
-            let mut hashmap = self.orders_without_claimed.write().await;
-            match hashmap.entry(auction_id) {
-                Entry::Occupied(order_vec) => {
-                    order_vec.into_mut().retain(|x| !orders.contains(x));
-                }
-                Entry::Vacant(_) => (),
+        if let Some(auction_orders) = self.orders.write().await.get_mut(&auction_id) {
+            for order in &orders {
+                auction_orders.remove(order);
             }
         }
     }
@@ -255,13 +251,14 @@ impl Orderbook {
         if orders.is_empty() {
             return true;
         }
-        let mut hashmap = self.orders_without_claimed.write().await;
-        match hashmap.entry(auction_id) {
-            Entry::Occupied(order_vec) => {
-                order_vec.into_mut().retain(|x| !orders.contains(x));
+        match self.orders.write().await.get_mut(&auction_id) {
+            Some(auction_orders) => {
+                for order in &orders {
+                    auction_orders.claim(order);
+                }
                 true
             }
-            Entry::Vacant(_) => false,
+            None => false,
         }
     }
     pub async fn get_initial_order(&self, auction_id: u64) -> Order {
@@ -282,12 +279,17 @@ impl Orderbook {
                 Err(err) => return Err(err),
             };
         {
-            let orders_hashmap = self.orders_display.read().await;
-            if let Some(orders) = orders_hashmap.get(&auction_id) {
-                bids = orders.to_vec();
-            } else {
-                bids = Vec::new();
-            }
+            let orders_hashmap = self.orders.read().await;
+            let mut price_points: Vec<PricePoint> = orders_hashmap
+                .get(&auction_id)
+                .into_iter()
+                .flat_map(AuctionOrders::iter)
+                .filter_map(|order| {
+                    order.convert_to_price_point(decimals_auctioning_token, decimals_bidding_token)
+                })
+                .collect();
+            price_points.sort();
+            bids = price_points;
         }
         let initial_order = vec![self.get_initial_order(auction_id).await];
         let asks: Vec<PricePoint> = initial_order
@@ -297,19 +299,53 @@ impl Orderbook {
                 buy_amount: order.buy_amount,
                 user_id: order.user_id,
             })
-            .map(|order| {
+            .filter_map(|order| {
                 order
                     .convert_to_price_point(decimals_bidding_token, decimals_auctioning_token)
                     // << invert price for unified representation of different orders.
-                    .invert_price()
+                    .map(|price_point| price_point.invert_price())
             })
             .collect();
         Ok(OrderbookDisplay { asks, bids })
     }
+    /// Cumulative bid volume of an auction in the given number of price buckets.
+    pub async fn get_depth_chart(
+        &self,
+        auction_id: u64,
+        number_of_buckets: usize,
+    ) -> Result<DepthChart> {
+        let (decimals_auctioning_token, decimals_bidding_token) =
+            self.get_decimals(auction_id).await?;
+        let bids = self.get_order_book_display(auction_id).await?.bids;
+        let (clearing_order, _, bidding_amount) =
+            self.get_clearing_order_and_volume(auction_id).await?;
+        let clearing_point = DepthPoint {
+            price: clearing_order
+                .convert_to_price_point(decimals_auctioning_token, decimals_bidding_token)
+                .map(|price_point| price_point.exact_price)
+                .unwrap_or_default(),
+            cumulative_volume: Decimal::new(bidding_amount, decimals_bidding_token.as_u32()),
+        };
+        let min_funding_threshold = match self.auction_details.read().await.get(&auction_id) {
+            Some(details) => Decimal::new(
+                details.min_funding_threshold,
+                decimals_bidding_token.as_u32(),
+            ),
+            None => Decimal::zero(),
+        };
+        Ok(DepthChart::new(
+            &bids,
+            number_of_buckets,
+            clearing_point,
+            min_funding_threshold,
+        ))
+    }
     pub async fn get_orders(&self, auction_id: u64) -> Vec<Order> {
         let hashmap = self.orders.read().await;
-        let empty_vec = Vec::new();
-        hashmap.get(&auction_id).unwrap_or(&empty_vec).clone()
+        hashmap
+            .get(&auction_id)
+            .map(|auction_orders| auction_orders.iter().copied().collect())
+            .unwrap_or_default()
     }
     pub async fn get_used_auctions(&self, user_id: u64) -> HashSet<u64> {
         let hashmap = self.auction_participation.read().await;
@@ -321,15 +357,25 @@ impl Orderbook {
         let hashmap = self.users.read().await;
         Ok(*hashmap.get(&user).unwrap_or(&(0_u64)))
     }
+    pub async fn get_user(&self, user_id: u64) -> Result<User> {
+        let hashmap = self.user_addresses.read().await;
+        match hashmap.get(&user_id) {
+            Some(address) => Ok(User {
+                address: *address,
+                user_id,
+            }),
+            None => Err(anyhow!("User with the id {:} does not exist", user_id)),
+        }
+    }
 
     pub async fn get_user_orders(&self, auction_id: u64, user: H160) -> Vec<Order> {
         let hashmap = self.users.read().await;
         let user_id = *hashmap.get(&user).unwrap_or(&(0_u64));
         let hashmap = self.orders.read().await;
-        let empty_vec = Vec::new();
-        let current_orders = hashmap.get(&auction_id).unwrap_or(&empty_vec);
-        current_orders
-            .iter()
+        hashmap
+            .get(&auction_id)
+            .into_iter()
+            .flat_map(AuctionOrders::iter)
             .filter(|order| order.user_id == user_id)
             .copied()
             .collect()
@@ -341,11 +387,11 @@ impl Orderbook {
     ) -> Vec<Order> {
         let hashmap = self.users.read().await;
         let user_id = *hashmap.get(&user).unwrap_or(&(0_u64));
-        let hashmap = self.orders_without_claimed.read().await;
-        let empty_vec = Vec::new();
-        let current_orders = hashmap.get(&auction_id).unwrap_or(&empty_vec);
-        current_orders
-            .iter()
+        let hashmap = self.orders.read().await;
+        hashmap
+            .get(&auction_id)
+            .into_iter()
+            .flat_map(AuctionOrders::open_orders)
             .filter(|order| order.user_id == user_id)
             .copied()
             .collect()
@@ -354,135 +400,471 @@ impl Orderbook {
         &self,
         auction_id: u64,
     ) -> Result<(Order, U256, U256)> {
-        // code is one to one copy of smart contract, hence no extensive testing
-        let orders = self.get_orders(auction_id).await;
-        let initial_order = self.get_initial_order(auction_id).await;
-        let mut current_bid_sum = U256::zero();
-        let mut current_order = Order::default();
-
-        for order in orders {
-            current_order = order;
-            current_bid_sum = current_bid_sum
-                .checked_add(order.sell_amount)
-                .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?;
-            if current_bid_sum
-                .checked_mul(order.buy_amount)
-                .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?
-                .ge(&initial_order
-                    .sell_amount
-                    .checked_mul(order.sell_amount)
-                    .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?)
-            {
-                break;
+        if let Some(details) = self.auction_details.read().await.get(&auction_id) {
+            if details.settlement.is_settled {
+                let settlement = &details.settlement;
+                return Ok((
+                    settlement.clearing_price_order,
+                    settlement.volume_clearing_price_order,
+                    settlement.sold_bidding_tokens,
+                ));
             }
         }
-        if current_bid_sum.gt(&U256::zero())
-            && current_bid_sum
-                .checked_mul(current_order.buy_amount)
-                .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?
-                .ge(&initial_order
-                    .sell_amount
-                    .checked_mul(current_order.sell_amount)
-                    .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?)
-        {
-            let uncovered_bids = current_bid_sum
-                .checked_sub(
-                    initial_order
-                        .sell_amount
-                        .checked_mul(current_order.sell_amount)
-                        .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?
-                        .checked_div(current_order.buy_amount)
-                        .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?,
-                )
-                .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?;
-            if current_order.sell_amount.ge(&uncovered_bids) {
-                let sell_amount_clearing_order = current_order
-                    .sell_amount
-                    .checked_sub(uncovered_bids)
-                    .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?;
-                Ok((
-                    current_order,
-                    sell_amount_clearing_order,
-                    current_bid_sum
-                        .checked_sub(current_order.sell_amount)
-                        .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?
-                        .checked_add(sell_amount_clearing_order)
-                        .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?,
-                ))
-            } else {
-                let clearing_order = Order {
-                    sell_amount: current_bid_sum
-                        .checked_sub(current_order.sell_amount)
-                        .unwrap(),
-                    buy_amount: initial_order.sell_amount,
-                    user_id: 0_u64,
-                };
-                Ok((
-                    clearing_order,
-                    U256::zero(),
-                    current_bid_sum
-                        .checked_sub(current_order.sell_amount)
-                        .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?,
-                ))
-            }
-        } else if current_bid_sum.gt(&initial_order.buy_amount) {
-            let clearing_order = Order {
-                buy_amount: initial_order.sell_amount,
-                sell_amount: current_bid_sum,
-                user_id: 0_u64,
-            };
-            Ok((clearing_order, U256::zero(), current_bid_sum))
-        } else {
-            let clearing_order = Order {
-                buy_amount: initial_order.sell_amount,
-                sell_amount: initial_order.buy_amount,
-                user_id: 0_u64,
-            };
-            let clearing_volume = current_bid_sum
-                .checked_mul(initial_order.sell_amount)
-                .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?
-                .checked_div(initial_order.buy_amount)
-                .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?;
-            Ok((clearing_order, clearing_volume, current_bid_sum))
+        let initial_order = self.get_initial_order(auction_id).await;
+        let accumulated_bids = match self.orders.read().await.get(&auction_id) {
+            Some(auction_orders) => auction_orders.accumulated_bids(initial_order)?,
+            None => AccumulatedBids::default(),
+        };
+        clearing_order_and_volume(accumulated_bids, initial_order)
+    }
+    /// Calculates the clearing order, as if the given bid was placed in the auction. Bids,
+    /// which the contract would reject, are rejected with the reason.
+    pub async fn simulate_bid(&self, auction_id: u64, bid: Order) -> Result<BidSimulation> {
+        let details = self.get_auction_with_details(auction_id).await?;
+        if details.settlement.is_settled {
+            return Err(anyhow!(
+                "Auction with the id {:} is already settled",
+                auction_id
+            ));
         }
+        // the auction phase is checked at the same time as its status
+        let timestamp = self.status_timestamp().await;
+        let mut orders = self.get_orders(auction_id).await;
+        let position = check_bid(&details, &orders, &bid, timestamp)?;
+        if let Some(position) = position {
+            orders.insert(position, bid);
+        }
+        let initial_order = self.get_initial_order(auction_id).await;
+        let (clearing_order, volume_clearing_order, bidding_amount) =
+            calculate_clearing_order_and_volume(&orders, initial_order)?;
+        let order_simulation = bid.simulate_claim(
+            &clearing_order,
+            volume_clearing_order,
+            details.min_funding_threshold <= bidding_amount,
+        )?;
+        Ok(BidSimulation {
+            clearing_order,
+            volume_clearing_order,
+            bidding_amount,
+            is_in_the_money: bid <= clearing_order,
+            is_inserted: position.is_some(),
+            order_simulation,
+        })
+    }
+    /// Simulates the claiming of all orders of a user, as if the auction was settled at
+    /// the current clearing order.
+    pub async fn simulate_user_orders(
+        &self,
+        auction_id: u64,
+        user: H160,
+    ) -> Result<Vec<OrderSimulation>> {
+        let min_funding_threshold = self
+            .get_auction_with_details(auction_id)
+            .await?
+            .min_funding_threshold;
+        let (clearing_order, volume_clearing_order, bidding_amount) =
+            self.get_clearing_order_and_volume(auction_id).await?;
+        let min_funding_threshold_reached = min_funding_threshold <= bidding_amount;
+        self.get_user_orders(auction_id, user)
+            .await
+            .iter()
+            .map(|order| {
+                order.simulate_claim(
+                    &clearing_order,
+                    volume_clearing_order,
+                    min_funding_threshold_reached,
+                )
+            })
+            .collect()
     }
     pub async fn get_previous_order(&self, auction_id: u64, order: Order) -> Order {
-        let order_hashmap = self.orders_without_claimed.read().await;
-        let empty_order_vec = Vec::new();
-        let order_vec = order_hashmap.get(&auction_id).unwrap_or(&empty_order_vec);
-        let mut smaller_order: Order = *QUEUE_START;
-        for order_from_vec in order_vec {
-            if order_from_vec < &order && order_from_vec > &smaller_order {
-                smaller_order = *order_from_vec;
-            }
-        }
-        smaller_order
+        let order_hashmap = self.orders.read().await;
+        order_hashmap
+            .get(&auction_id)
+            .and_then(|auction_orders| auction_orders.previous_open_order(&order))
+            .filter(|previous_order| **previous_order > *QUEUE_START)
+            .copied()
+            .unwrap_or(*QUEUE_START)
     }
     pub async fn set_auction_details(
         &self,
         auction_id: u64,
-        details: AuctionDetails,
+        mut details: AuctionDetails,
     ) -> Result<()> {
+        details.status = details.status_at(self.status_timestamp().await);
         let mut auction_details = self.auction_details.write().await;
+        self.search_index.write().await.insert(&details);
         auction_details.insert(auction_id, details);
         Ok(())
     }
+    /// Restores the state persisted by previous runs and returns the cursor of the last
+    /// block whose events are contained in it, if there is any. The updates of the last
+    /// synced block ranges are restored as well, such that they can be reverted.
+    pub async fn load_from_database(
+        &self,
+        database: &Database,
+        chain_id: u32,
+    ) -> Result<Option<SyncCursor>> {
+        let persisted = database.load_orderbook(chain_id).await?;
+        *self.applied_updates.write().await = persisted.checkpoints;
+        for auction_details in persisted.auctions {
+            self.set_auction_details(auction_details.auction_id, auction_details)
+                .await?;
+        }
+        self.insert_users(persisted.users).await;
+        let mut orders: HashMap<u64, Vec<Order>> = HashMap::new();
+        let mut claimed_orders: HashMap<u64, Vec<Order>> = HashMap::new();
+        for (auction_id, order, claimed) in persisted.orders {
+            orders.entry(auction_id).or_default().push(order);
+            if claimed {
+                claimed_orders.entry(auction_id).or_default().push(order);
+            }
+        }
+        for (auction_id, orders) in orders {
+            self.insert_orders(auction_id, orders).await;
+            self.remove_claimed_orders(
+                auction_id,
+                claimed_orders.remove(&auction_id).unwrap_or_default(),
+            )
+            .await;
+        }
+        let mut dead_letters = self.dead_letters.write().await;
+        for dead_letter in database.load_dead_letters(chain_id).await? {
+            // the auction might have been recovered without deleting its dead letter
+            let is_recovered = {
+                let auction_details = self.auction_details.read().await;
+                let details = auction_details.get(&dead_letter.auction_id);
+                match dead_letter.kind {
+                    DeadLetterKind::Creation => details.is_some(),
+                    DeadLetterKind::Clearing => {
+                        details.map_or(true, |details| details.settlement.is_settled)
+                    }
+                }
+            };
+            if is_recovered {
+                database
+                    .delete_dead_letter(chain_id, dead_letter.auction_id)
+                    .await?;
+            } else {
+                dead_letters.insert(dead_letter.auction_id, dead_letter);
+            }
+        }
+        // the persisted clearing price info might be outdated
+        self.stale_auctions
+            .write()
+            .await
+            .extend(self.auction_details.read().await.keys().copied());
+        Ok(persisted.cursor)
+    }
     pub async fn get_max_auction_id(&self) -> Result<u64> {
         let auction_details = self.auction_details.read().await;
         let max_auction_id = auction_details.keys().max().unwrap_or(&0_u64);
         Ok(*max_auction_id)
     }
+    /// Current state of an order in the orderbook of its auction.
+    async fn order_state(&self, auction_id: u64, order: &Order) -> OrderState {
+        match self.orders.read().await.get(&auction_id) {
+            Some(auction_orders) if auction_orders.is_open(order) => OrderState::Open,
+            Some(auction_orders) if auction_orders.is_claimed(order) => OrderState::Claimed,
+            _ => OrderState::Absent,
+        }
+    }
+    /// Applies the order events of an auction in the order in which they were emitted.
+    pub async fn apply_order_events(&self, auction_id: u64, events: &[OrderEvent]) {
+        self.transition_orders(auction_id, events.iter(), OrderState::apply)
+            .await;
+    }
+    /// Reverts the order events of an auction, starting with the most recent one, and
+    /// returns the orders removed from the orderbook.
+    pub async fn revert_order_events(&self, auction_id: u64, events: &[OrderEvent]) -> Vec<Order> {
+        self.transition_orders(auction_id, events.iter().rev(), OrderState::revert)
+            .await
+    }
+    /// Passes each order through the states given by the events and writes only the final
+    /// state of each order to the orderbook, such that e.g. an order placed, cancelled and
+    /// placed again is contained once. Returns the removed orders.
+    async fn transition_orders(
+        &self,
+        auction_id: u64,
+        events: impl Iterator<Item = &OrderEvent>,
+        step: fn(OrderState, OrderEventKind) -> Option<OrderState>,
+    ) -> Vec<Order> {
+        // (order, initial state, current state)
+        let mut states: Vec<(Order, OrderState, OrderState)> = Vec::new();
+        for event in events {
+            let index = match states
+                .iter()
+                .position(|(order, _, _)| *order == event.order)
+            {
+                Some(index) => index,
+                None => {
+                    let state = self.order_state(auction_id, &event.order).await;
+                    states.push((event.order, state, state));
+                    states.len() - 1
+                }
+            };
+            let state = &mut states[index].2;
+            match step(*state, event.kind) {
+                Some(next_state) => *state = next_state,
+                None => tracing::warn!(
+                    "ignoring {:?} event of order {:?} of auction {} in state {:?}",
+                    event.kind,
+                    event.order,
+                    auction_id,
+                    state
+                ),
+            }
+        }
+        let mut inserted_orders = Vec::new();
+        let mut removed_orders = Vec::new();
+        let mut claimed_orders = Vec::new();
+        let mut unclaimed_orders = Vec::new();
+        for (order, initial_state, state) in states {
+            match (initial_state, state) {
+                (OrderState::Absent, OrderState::Absent)
+                | (OrderState::Open, OrderState::Open)
+                | (OrderState::Claimed, OrderState::Claimed) => {}
+                (OrderState::Absent, OrderState::Open) => inserted_orders.push(order),
+                (OrderState::Absent, OrderState::Claimed) => {
+                    inserted_orders.push(order);
+                    claimed_orders.push(order);
+                }
+                (OrderState::Open, OrderState::Absent)
+                | (OrderState::Claimed, OrderState::Absent) => removed_orders.push(order),
+                (OrderState::Open, OrderState::Claimed) => claimed_orders.push(order),
+                (OrderState::Claimed, OrderState::Open) => unclaimed_orders.push(order),
+            }
+        }
+        self.insert_orders(auction_id, inserted_orders).await;
+        self.remove_orders(auction_id, removed_orders.clone()).await;
+        self.remove_claimed_orders(auction_id, claimed_orders).await;
+        self.restore_claimed_orders(auction_id, unclaimed_orders)
+            .await;
+        removed_orders
+    }
+    pub async fn restore_claimed_orders(&self, auction_id: u64, orders: Vec<Order>) {
+        if let Some(auction_orders) = self.orders.write().await.get_mut(&auction_id) {
+            for order in &orders {
+                auction_orders.unclaim(order);
+            }
+        }
+    }
+    pub async fn remove_users(&self, users: Vec<User>) {
+        let mut hashmap = self.users.write().await;
+        let mut reverse_hashmap = self.user_addresses.write().await;
+        for user in users {
+            hashmap.remove(&user.address);
+            reverse_hashmap.remove(&user.user_id);
+        }
+    }
+    /// Removes the participation of users in an auction, if they have no orders left in it.
+    async fn remove_participation_without_orders(&self, auction_id: u64, user_ids: HashSet<u64>) {
+        let remaining_orders = self.get_orders(auction_id).await;
+        let mut hashmap = self.auction_participation.write().await;
+        for user_id in user_ids {
+            if remaining_orders
+                .iter()
+                .any(|order| order.user_id == user_id)
+            {
+                continue;
+            }
+            if let Entry::Occupied(mut auction_set) = hashmap.entry(user_id) {
+                auction_set.get_mut().remove(&auction_id);
+            }
+        }
+    }
+    pub async fn remove_auction(&self, auction_id: u64) {
+        self.auction_details.write().await.remove(&auction_id);
+        self.search_index.write().await.remove(auction_id);
+        self.orders.write().await.remove(&auction_id);
+        for auction_set in self.auction_participation.write().await.values_mut() {
+            auction_set.remove(&auction_id);
+        }
+    }
+    /// Drops the complete state, such that it can be synced again from the beginning.
+    pub async fn reset(&self) {
+        self.orders.write().await.clear();
+        self.users.write().await.clear();
+        self.user_addresses.write().await.clear();
+        self.auction_participation.write().await.clear();
+        self.auction_details.write().await.clear();
+        self.search_index.write().await.clear();
+        self.dead_letters.write().await.clear();
+        self.stale_auctions.write().await.clear();
+        *self.block_timestamp.write().await = 0;
+        self.applied_updates.write().await.clear();
+    }
+    /// Undoes the updates of a block range in the reverse order of their application.
+    async fn revert_applied_updates(&self, updates: AppliedUpdates) {
+        let order_updates = updates.order_updates;
+        let touched_auctions: HashSet<u64> = order_updates
+            .touched_auctions()
+            .chain(updates.new_auctions.iter().copied())
+            .collect();
+        for &auction_id in &touched_auctions {
+            self.update_feed.publish(AuctionUpdate {
+                auction_id,
+                block_number: updates.from_block - 1,
+                event: AuctionEvent::Reorg,
+            });
+        }
+        self.stale_auctions.write().await.extend(
+            touched_auctions
+                .iter()
+                .filter(|auction_id| !updates.new_auctions.contains(auction_id)),
+        );
+        let auction_ids: HashSet<u64> = order_updates
+            .order_events
+            .iter()
+            .map(|event| event.auction_id)
+            .collect();
+        for auction_id in auction_ids {
+            let removed_orders = self
+                .revert_order_events(
+                    auction_id,
+                    &order_updates.order_events_of_auction(auction_id),
+                )
+                .await;
+            let user_ids = removed_orders.iter().map(|order| order.user_id).collect();
+            self.remove_participation_without_orders(auction_id, user_ids)
+                .await;
+        }
+        self.remove_users(order_updates.users_added).await;
+        for clearing in order_updates.auctions_cleared {
+            self.update_settlement_of_details(clearing.auction_id, AuctionSettlement::default())
+                .await;
+        }
+        for auction_id in updates.new_auctions {
+            self.remove_auction(auction_id).await;
+        }
+    }
+    /// Reverts the updates of all block ranges ending after `block_number`.
+    pub async fn revert_updates_after(
+        &self,
+        block_number: u64,
+        block_hash: H256,
+        chain_id: u32,
+        database: Option<&Database>,
+    ) -> Result<()> {
+        let mut applied_updates = self.applied_updates.write().await;
+        let first_reverted = applied_updates
+            .iter()
+            .position(|updates| updates.order_updates.last_block_handled > block_number)
+            .unwrap_or(applied_updates.len());
+        if let Some(database) = database {
+            database
+                .revert_updates(
+                    chain_id,
+                    &applied_updates[first_reverted..],
+                    SyncCursor {
+                        last_block: block_number,
+                        block_hash: Some(block_hash),
+                    },
+                )
+                .await?;
+        }
+        let reverted_updates = applied_updates.split_off(first_reverted);
+        let mut dead_letters = self.dead_letters.write().await;
+        // the events of these dead letters are read again
+        dead_letters.retain(|_, dead_letter| dead_letter.block_number <= block_number);
+        if let Some(database) = database {
+            database
+                .delete_dead_letters_after(chain_id, block_number)
+                .await?;
+        }
+        for updates in reverted_updates.into_iter().rev() {
+            tracing::info!(
+                "Reverting events from block {} to block {} due to a reorg",
+                updates.from_block,
+                updates.order_updates.last_block_handled
+            );
+            for dead_letter in updates
+                .recovered_auctions
+                .iter()
+                .filter(|dead_letter| dead_letter.block_number <= block_number)
+            {
+                if let Some(database) = database {
+                    database.upsert_dead_letter(chain_id, dead_letter).await?;
+                }
+                dead_letters.insert(dead_letter.auction_id, dead_letter.clone());
+            }
+            self.revert_applied_updates(updates).await;
+        }
+        Ok(())
+    }
+    /// Checks whether already processed blocks were reorganized and reverts their events.
+    /// If the reorg is deeper than the tracked history, the state is reset and synced
+    /// again starting from `first_block`.
+    pub async fn handle_reorg(
+        &self,
+        event_reader: &EventReader,
+        last_block_considered: &mut u64,
+        first_block: u64,
+        chain_id: u32,
+        database: Option<&Database>,
+    ) -> Result<()> {
+        match event_reader.check_for_reorg().await? {
+            ReorgStatus::NoReorg => {}
+            ReorgStatus::RevertAfter(block_number) => {
+                let (block_hash, _) = event_reader.get_block_hashes(block_number).await?;
+                self.revert_updates_after(block_number, block_hash, chain_id, database)
+                    .await?;
+                event_reader.forget_blocks_after(block_number);
+                *last_block_considered = block_number;
+            }
+            ReorgStatus::ReorgTooDeep => {
+                if let Some(database) = database {
+                    if let Some((block_number, block_hash)) =
+                        last_canonical_checkpoint(event_reader, database, chain_id).await?
+                    {
+                        tracing::warn!(
+                            "Reorg deeper than the last {} synced block ranges detected, reverting the updates after block {}",
+                            MAX_CHECKPOINTS,
+                            block_number
+                        );
+                        // the updates of older ranges are only kept in their checkpoints
+                        *self.applied_updates.write().await = database
+                            .load_checkpoints_after(chain_id, block_number)
+                            .await?;
+                        self.revert_updates_after(
+                            block_number,
+                            block_hash,
+                            chain_id,
+                            Some(database),
+                        )
+                        .await?;
+                        event_reader.forget_blocks_after(block_number);
+                        event_reader.record_block_hash(block_number, block_hash);
+                        *last_block_considered = block_number;
+                        return Ok(());
+                    }
+                }
+                tracing::error!(
+                    "Reorg deeper than the last {} synced block ranges detected, syncing again from block {}",
+                    MAX_CHECKPOINTS,
+                    first_block
+                );
+                if let Some(database) = database {
+                    database.delete_orderbook(chain_id).await?;
+                }
+                self.reset().await;
+                event_reader.forget_blocks_after(0);
+                *last_block_considered = first_block;
+            }
+        }
+        Ok(())
+    }
     pub async fn run_maintenance(
         &self,
         event_reader: &EventReader,
-        the_graph_reader: &mut UniswapSubgraphClient,
+        price_oracle: &dyn PriceOracle,
         last_block_considered: &mut u64,
-        reorg_protection: bool,
         chain_id: u32,
         current_block: u64,
+        database: Option<&Database>,
     ) -> Result<()> {
         let (from_block, to_block);
-        match event_reader.get_to_block(*last_block_considered, reorg_protection, current_block) {
+        match event_reader.get_to_block(*last_block_considered, current_block) {
             Ok(return_data) => {
                 from_block = return_data.0;
                 to_block = return_data.1
@@ -495,73 +877,229 @@ impl Orderbook {
                 return Ok(());
             }
         }
-
-        let new_auctions = match event_reader
-            .get_auction_updates(from_block, to_block, chain_id)
-            .await
-        {
-            Ok(auction_updates) => auction_updates,
+        let block_hash = match event_reader.get_block_hashes(to_block).await {
+            Ok((block_hash, _)) => block_hash,
             Err(err) => {
-                tracing::info!("get_order_updates was not successful with error: {:}", err);
+                tracing::info!("get_block_hashes was not successful with error: {:}", err);
                 return Ok(());
             }
         };
-        for auction_details in new_auctions {
-            self.set_auction_details(auction_details.auction_id, auction_details)
-                .await?;
-        }
-        let new_orders: Vec<OrderWithAuctionId>;
-        let canceled_orders: Vec<OrderWithAuctionId>;
-        let new_claimed_orders: Vec<OrderWithAuctionId>;
-        let new_users: Vec<User>;
-        match event_reader.get_order_updates(from_block, to_block).await {
-            Ok(order_updates) => {
-                new_orders = order_updates.orders_added;
-                canceled_orders = order_updates.orders_removed;
-                new_claimed_orders = order_updates.orders_claimed;
-                new_users = order_updates.users_added;
-            }
+        let block_timestamp = match event_reader.get_block_timestamp(to_block).await {
+            Ok(block_timestamp) => block_timestamp,
             Err(err) => {
-                tracing::info!("get_order_updates was not successful with error: {:}", err);
+                tracing::info!(
+                    "get_block_timestamp was not successful with error: {:}",
+                    err
+                );
                 return Ok(());
             }
-        }
-        self.insert_users(new_users).await;
+        };
 
-        let max_auction_id = self.get_max_auction_id().await?;
-        for auction_id in 1..=max_auction_id {
-            self.insert_orders(
-                auction_id,
-                new_orders
-                    .iter()
-                    .filter(|order_with_auction_id| order_with_auction_id.auction_id == auction_id)
-                    .map(|order_with_auction_id| order_with_auction_id.order)
-                    .collect(),
-            )
-            .await;
-            self.remove_orders(
-                auction_id,
-                canceled_orders
-                    .iter()
-                    .filter(|order_with_auction_id| order_with_auction_id.auction_id == auction_id)
-                    .map(|order_with_auction_id| order_with_auction_id.order)
-                    .collect(),
+        let events = match event_reader.get_events(from_block, to_block).await {
+            Ok(events) => events,
+            Err(err) => {
+                event_reader.record_range_failure(&err);
+                tracing::warn!("get_events was not successful with error: {:}", err);
+                return Ok(());
+            }
+        };
+        event_reader.record_range_success(from_block, to_block);
+        let auction_updates = event_reader.get_auction_updates(&events, chain_id).await;
+        let (mut order_updates, failed_clearings) =
+            event_reader.get_order_updates(&events, to_block).await;
+        let mut new_auctions = auction_updates.auctions;
+        let recovered_auctions = self
+            .retry_dead_letters(
+                event_reader,
+                from_block,
+                chain_id,
+                database,
+                &mut new_auctions,
+                &mut order_updates,
             )
             .await;
-            self.remove_claimed_orders(
-                auction_id,
-                new_claimed_orders
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        // Auctions, whose creation is quarantined, are not indexed yet. Their updates are read
+        // again once they are recovered, including their clearing.
+        let unindexed_auctions: HashSet<u64> = self
+            .dead_letters
+            .read()
+            .await
+            .values()
+            .filter(|dead_letter| {
+                dead_letter.kind == DeadLetterKind::Creation
+                    && !recovered_auctions
+                        .iter()
+                        .any(|recovered| recovered.auction_id == dead_letter.auction_id)
+            })
+            .map(|dead_letter| dead_letter.auction_id)
+            .chain(
+                auction_updates
+                    .failed
                     .iter()
-                    .filter(|order_with_auction_id| order_with_auction_id.auction_id == auction_id)
-                    .map(|order_with_auction_id| order_with_auction_id.order)
-                    .collect(),
+                    .map(|failed| failed.auction_id),
             )
-            .await;
-            self.sort_orders_without_claimed(auction_id).await;
-            self.sort_orders(auction_id).await;
-            self.sort_orders_display(auction_id).await;
+            .collect();
+        let failed_clearings = failed_clearings
+            .into_iter()
+            .filter(|failed| !unindexed_auctions.contains(&failed.auction_id))
+            .map(|failed| (DeadLetterKind::Clearing, failed));
+        let new_dead_letters: Vec<DeadLetter> = auction_updates
+            .failed
+            .into_iter()
+            .map(|failed| (DeadLetterKind::Creation, failed))
+            .chain(failed_clearings)
+            .map(|(kind, failed)| {
+                tracing::warn!(
+                    "quarantining the {} of auction {} after error: {:?}",
+                    kind.as_str(),
+                    failed.auction_id,
+                    failed.error
+                );
+                DeadLetter::new(
+                    failed.auction_id,
+                    kind,
+                    failed.block_number,
+                    format!("{:?}", failed.error),
+                    now,
+                )
+            })
+            .collect();
+        order_updates.retain_auctions(|auction_id| !unindexed_auctions.contains(&auction_id));
+        // The events are only applied, if they belong to the same chain as the previously
+        // applied ones and the range was not reorganized while fetching them. Otherwise,
+        // the reorg is handled before the next update.
+        match (
+            event_reader.extends_recorded_chain(from_block).await,
+            event_reader.get_block_hashes(to_block).await,
+        ) {
+            (Ok(true), Ok((block_hash_after_update, _)))
+                if block_hash_after_update == block_hash => {}
+            (Err(err), _) | (_, Err(err)) => {
+                tracing::info!(
+                    "block hash validation was not successful with error: {:}",
+                    err
+                );
+                return Ok(());
+            }
+            _ => {
+                tracing::info!(
+                    "Chain reorganized while updating from block {} to block {}",
+                    from_block,
+                    to_block
+                );
+                return Ok(());
+            }
+        }
+        let updates = AppliedUpdates {
+            from_block,
+            block_hash,
+            new_auctions: new_auctions
+                .iter()
+                .map(|auction_details| auction_details.auction_id)
+                .collect(),
+            order_updates,
+            recovered_auctions,
+        };
+        // The updates are persisted before they are applied, such that a failed write can be
+        // retried with the same block range without applying any event twice.
+        if let Some(database) = database {
             if let Err(err) = self
-                .update_clearing_price_info(the_graph_reader, auction_id, chain_id)
+                .persist_with_dead_letters(
+                    database,
+                    chain_id,
+                    &new_auctions,
+                    &updates,
+                    &new_dead_letters,
+                )
+                .await
+            {
+                tracing::info!("persist_updates was not successful with error: {:}", err);
+                return Ok(());
+            }
+        }
+        // The read histories of the recovered auctions are kept until they are persisted, such
+        // that a dropped range does not lose them.
+        event_reader.forget_auction_histories(
+            updates
+                .recovered_auctions
+                .iter()
+                .filter(|dead_letter| dead_letter.kind == DeadLetterKind::Creation)
+                .map(|dead_letter| dead_letter.auction_id),
+        );
+        {
+            let mut dead_letters = self.dead_letters.write().await;
+            for dead_letter in updates.recovered_auctions.iter() {
+                dead_letters.remove(&dead_letter.auction_id);
+            }
+            for auction_details in new_auctions.iter() {
+                dead_letters.remove(&auction_details.auction_id);
+            }
+            for dead_letter in new_dead_letters {
+                dead_letters.insert(dead_letter.auction_id, dead_letter);
+            }
+        }
+        for auction_details in new_auctions {
+            self.set_auction_details(auction_details.auction_id, auction_details)
+                .await?;
+        }
+        let new_users: Vec<User> = updates.order_updates.users_added.clone();
+        self.insert_users(new_users).await;
+        for clearing in &updates.order_updates.auctions_cleared {
+            self.update_settlement_of_details(clearing.auction_id, clearing.settlement.clone())
+                .await;
+        }
+        self.update_block_timestamp(block_timestamp).await;
+
+        // Only the clearing price info of auctions with new events is recomputed, next to
+        // the stale ones.
+        let touched_auctions: BTreeSet<u64> = updates
+            .order_updates
+            .touched_auctions()
+            .chain(updates.new_auctions.iter().copied())
+            .collect();
+        let mut auction_ids = touched_auctions.clone();
+        auction_ids.extend(std::mem::take(&mut *self.stale_auctions.write().await));
+        // The price history gets a point at each block with an event of an auction.
+        let mut event_blocks = BTreeMap::new();
+        let mut block_timestamps = BTreeMap::new();
+        if database.is_some() {
+            event_blocks = auction_event_blocks(
+                &EventReader::auction_creation_blocks(&events),
+                &updates.new_auctions,
+                &updates.recovered_auctions,
+                &updates.order_updates,
+            );
+            let blocks: BTreeSet<u64> = event_blocks
+                .values()
+                .flatten()
+                .copied()
+                .filter(|block_number| *block_number != to_block)
+                .collect();
+            match event_reader.get_block_timestamps(blocks).await {
+                Ok(timestamps) => {
+                    block_timestamps = timestamps;
+                    block_timestamps.insert(to_block, block_timestamp);
+                }
+                Err(err) => tracing::warn!(
+                    "get_block_timestamps was not successful with error: {:}",
+                    err
+                ),
+            }
+        }
+        let mut clearing_price_points = Vec::new();
+        for auction_id in auction_ids {
+            let order_events = updates.order_updates.order_events_of_auction(auction_id);
+            let blocks = event_blocks.remove(&auction_id).unwrap_or_default();
+            let points = self
+                .apply_order_events_by_block(auction_id, &order_events, &blocks, &block_timestamps)
+                .await;
+            clearing_price_points.extend(points.into_iter().map(|point| (auction_id, point)));
+            if let Err(err) = self
+                .update_clearing_price_info(price_oracle, auction_id)
                 .await
             {
                 tracing::debug!(
@@ -570,46 +1108,287 @@ impl Orderbook {
                     auction_id
                 )
             };
+            if self.needs_revaluation(auction_id).await {
+                self.stale_auctions.write().await.insert(auction_id);
+            }
+            if touched_auctions.contains(&auction_id) {
+                self.publish_auction_updates(auction_id, to_block, order_events)
+                    .await;
+            }
+        }
+        if let Some(database) = database {
+            record_clearing_prices(database, chain_id, &clearing_price_points).await;
+        }
+        {
+            let mut applied_updates = self.applied_updates.write().await;
+            applied_updates.push(updates);
+            if applied_updates.len() > MAX_CHECKPOINTS {
+                let expired = applied_updates.len() - MAX_CHECKPOINTS;
+                applied_updates.drain(..expired);
+            }
         }
+        event_reader.record_block_hash(to_block, block_hash);
         *last_block_considered = to_block;
         Ok(())
     }
+    /// Tries to read the quarantined auctions that are due for a retry. The details and the
+    /// history of the recovered auctions are added to the updates of the current block range
+    /// and their dead letters are returned.
+    async fn retry_dead_letters(
+        &self,
+        event_reader: &EventReader,
+        from_block: u64,
+        chain_id: u32,
+        database: Option<&Database>,
+        new_auctions: &mut Vec<AuctionDetails>,
+        order_updates: &mut OrderUpdates,
+    ) -> Vec<DeadLetter> {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let due_dead_letters: Vec<DeadLetter> = self
+            .dead_letters
+            .read()
+            .await
+            .values()
+            .filter(|dead_letter| dead_letter.is_due(now))
+            .cloned()
+            .collect();
+        let mut recovered_auctions = Vec::new();
+        let mut history = OrderUpdates::default();
+        for mut dead_letter in due_dead_letters {
+            let recovery = match dead_letter.kind {
+                DeadLetterKind::Creation => event_reader
+                    .get_auction_history(
+                        dead_letter.auction_id,
+                        dead_letter.block_number,
+                        from_block - 1,
+                        chain_id,
+                    )
+                    .await
+                    .map(|(details, auction_history)| (Some(details), auction_history)),
+                DeadLetterKind::Clearing => event_reader
+                    .get_auction_clearing(dead_letter.auction_id, dead_letter.block_number)
+                    .await
+                    .map(|clearing| {
+                        let auction_history = OrderUpdates {
+                            auctions_cleared: vec![clearing],
+                            ..Default::default()
+                        };
+                        (None, auction_history)
+                    }),
+            };
+            match recovery {
+                Ok((details, auction_history)) => {
+                    tracing::info!(
+                        "recovered the {} of quarantined auction {}",
+                        dead_letter.kind.as_str(),
+                        dead_letter.auction_id
+                    );
+                    new_auctions.extend(details);
+                    history.append(auction_history);
+                    recovered_auctions.push(dead_letter);
+                }
+                Err(err) => {
+                    dead_letter.record_failure(format!("{:?}", err), now);
+                    tracing::warn!(
+                        "retrying quarantined auction {} failed for the {}. time: {:?}",
+                        dead_letter.auction_id,
+                        dead_letter.attempts,
+                        err
+                    );
+                    if let Some(database) = database {
+                        if let Err(err) = database.upsert_dead_letter(chain_id, &dead_letter).await
+                        {
+                            tracing::warn!("upsert_dead_letter failed: {:?}", err);
+                        }
+                    }
+                    self.dead_letters
+                        .write()
+                        .await
+                        .insert(dead_letter.auction_id, dead_letter);
+                }
+            }
+        }
+        // the history precedes the events of the current block range
+        history.append(std::mem::take(order_updates));
+        *order_updates = history;
+        recovered_auctions
+    }
+    /// Persists the updates of a block range and the changes of the quarantined auctions.
+    /// New dead letters are written first and obsolete ones deleted last, hence a failure
+    /// in between leaves at most dead letters of auctions that are already indexed, which
+    /// are dropped when loading the orderbook.
+    #[allow(clippy::too_many_arguments)]
+    async fn persist_with_dead_letters(
+        &self,
+        database: &Database,
+        chain_id: u32,
+        new_auctions: &[AuctionDetails],
+        updates: &AppliedUpdates,
+        new_dead_letters: &[DeadLetter],
+    ) -> Result<()> {
+        for dead_letter in new_dead_letters {
+            database.upsert_dead_letter(chain_id, dead_letter).await?;
+        }
+        database
+            .persist_updates(chain_id, new_auctions, updates)
+            .await?;
+        for dead_letter in &updates.recovered_auctions {
+            database
+                .delete_dead_letter(chain_id, dead_letter.auction_id)
+                .await?;
+        }
+        Ok(())
+    }
+    /// Applies the order events of an auction block by block and returns the points of the
+    /// given blocks in its price history. Blocks without a known timestamp get no point.
+    async fn apply_order_events_by_block(
+        &self,
+        auction_id: u64,
+        order_events: &[OrderEvent],
+        blocks: &BTreeSet<u64>,
+        block_timestamps: &BTreeMap<u64, u64>,
+    ) -> Vec<ClearingPricePoint> {
+        let mut points = Vec::new();
+        let mut remaining_events = order_events;
+        for block_number in blocks {
+            let block_event_count = remaining_events
+                .iter()
+                .take_while(|event| event.block_number <= *block_number)
+                .count();
+            let (block_events, later_events) = remaining_events.split_at(block_event_count);
+            self.apply_order_events(auction_id, block_events).await;
+            remaining_events = later_events;
+            let timestamp = match block_timestamps.get(block_number) {
+                Some(timestamp) => *timestamp,
+                None => continue,
+            };
+            match self
+                .clearing_price_point(auction_id, *block_number, timestamp)
+                .await
+            {
+                Ok(point) => points.push(point),
+                Err(err) => tracing::debug!(
+                    "error while calculating the clearing price: {:} for auction id {:} at block {:}",
+                    err,
+                    auction_id,
+                    block_number
+                ),
+            }
+        }
+        self.apply_order_events(auction_id, remaining_events).await;
+        points
+    }
+    /// Point of the given block in the price history of an auction, whose order events are
+    /// applied up to the block. The settlement is only considered from its block on.
+    async fn clearing_price_point(
+        &self,
+        auction_id: u64,
+        block_number: u64,
+        timestamp: u64,
+    ) -> Result<ClearingPricePoint> {
+        let details = self.get_auction_with_details(auction_id).await?;
+        let settlement = &details.settlement;
+        let (clearing_order, _, bidding_amount) = if settlement.is_settled
+            && settlement.settlement_block <= block_number
+        {
+            (
+                settlement.clearing_price_order,
+                settlement.volume_clearing_price_order,
+                settlement.sold_bidding_tokens,
+            )
+        } else {
+            let initial_order = self.get_initial_order(auction_id).await;
+            let accumulated_bids = match self.orders.write().await.get_mut(&auction_id) {
+                Some(auction_orders) => auction_orders.update_accumulated_bids(initial_order)?,
+                None => AccumulatedBids::default(),
+            };
+            clearing_order_and_volume(accumulated_bids, initial_order)?
+        };
+        details
+            .clearing_price_point(block_number, timestamp, &clearing_order, bidding_amount)
+            .ok_or_else(|| {
+                anyhow!(
+                    "clearing order {:?} has no representable price",
+                    clearing_order
+                )
+            })
+    }
+    pub async fn get_dead_letters(&self) -> Vec<DeadLetter> {
+        self.dead_letters.read().await.values().cloned().collect()
+    }
+    async fn publish_auction_updates(
+        &self,
+        auction_id: u64,
+        block_number: u64,
+        order_events: Vec<OrderEvent>,
+    ) {
+        let events = order_events.into_iter().map(|event| match event.kind {
+            OrderEventKind::Placed => AuctionEvent::NewOrder { order: event.order },
+            OrderEventKind::Cancelled => AuctionEvent::OrderCancellation { order: event.order },
+            OrderEventKind::Claimed => AuctionEvent::OrderClaim { order: event.order },
+        });
+        for event in events {
+            self.update_feed.publish(AuctionUpdate {
+                auction_id,
+                block_number,
+                event,
+            });
+        }
+        if let Ok((clearing_order, volume_clearing_order, bidding_amount)) =
+            self.get_clearing_order_and_volume(auction_id).await
+        {
+            self.update_feed.publish(AuctionUpdate {
+                auction_id,
+                block_number,
+                event: AuctionEvent::ClearingPriceUpdate {
+                    clearing_order,
+                    volume_clearing_order,
+                    bidding_amount,
+                },
+            });
+        }
+    }
     pub async fn update_clearing_price_info(
         &self,
-        the_graph_reader: &mut UniswapSubgraphClient,
+        price_oracle: &dyn PriceOracle,
         auction_id: u64,
-        chain_id: u32,
     ) -> Result<()> {
+        let (decimals_auctioning_token, decimals_bidding_token) =
+            self.get_decimals(auction_id).await?;
+        let initial_order = self.get_initial_order(auction_id).await;
+        if let Some(auction_orders) = self.orders.write().await.get_mut(&auction_id) {
+            auction_orders.update_accumulated_bids(initial_order)?;
+        }
         let new_clearing_price = self.get_clearing_order_and_volume(auction_id).await?;
-        let decimals_auctioning_token;
-        let decimals_bidding_token;
+        if let Some(price_point) = new_clearing_price
+            .0
+            .convert_to_price_point(decimals_auctioning_token, decimals_bidding_token)
         {
-            let reading_guard = self.auction_details.read().await;
-            decimals_auctioning_token = reading_guard
-                .get(&auction_id)
-                .expect("auction not yet initialized in backend")
-                .decimals_auctioning_token;
-
-            decimals_bidding_token = reading_guard
-                .get(&auction_id)
-                .expect("auction not yet initialized in backend")
-                .decimals_bidding_token;
+            self.update_current_price_of_details(auction_id, price_point)
+                .await?;
         }
-        self.update_current_price_of_details(
-            auction_id,
-            new_clearing_price
-                .0
-                .convert_to_price_point(decimals_auctioning_token, decimals_bidding_token)
-                .price,
-        )
-        .await?;
         self.update_current_bidding_amount_of_details(auction_id, new_clearing_price.2)
             .await?;
         self.update_interest_score(auction_id).await?;
-        self.update_usd_amount_traded_of_details(the_graph_reader, auction_id, chain_id)
+        self.update_usd_amount_traded_of_details(price_oracle, auction_id)
             .await?;
         Ok(())
     }
+    /// Whether the clearing price info of an auction might change without new events, as
+    /// its bidding amount is not yet valued at the price of a day that is over.
+    async fn needs_revaluation(&self, auction_id: u64) -> bool {
+        match self.auction_details.read().await.get(&auction_id) {
+            Some(details) => {
+                !is_day_over(details.end_time_timestamp)
+                    || (details.exact_usd_amount_traded.is_zero()
+                        && !details.current_bidding_amount.is_zero())
+            }
+            None => false,
+        }
+    }
     pub async fn get_most_interesting_auctions(
         &self,
         number_of_auctions: u64,
@@ -618,12 +1397,7 @@ impl Orderbook {
         let mut non_closed_auctions: Vec<AuctionDetails> = Vec::new();
         for auction_id in auction_details_hashmap.keys() {
             let auction_details = auction_details_hashmap.get(auction_id).unwrap();
-            if auction_details.end_time_timestamp
-                > SystemTime::now()
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs()
-            {
+            if !auction_details.status.is_closed() {
                 non_closed_auctions.push(auction_details.clone());
             }
         }
@@ -642,20 +1416,11 @@ impl Orderbook {
         let mut closed_auctions: Vec<AuctionDetails> = Vec::new();
         for auction_id in auction_details_hashmap.keys() {
             let auction_details = auction_details_hashmap.get(auction_id).unwrap();
-            if auction_details.end_time_timestamp
-                < SystemTime::now()
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs()
-            {
+            if auction_details.status.is_closed() {
                 closed_auctions.push(auction_details.clone());
             }
         }
-        closed_auctions.sort_by(|a, b| {
-            a.usd_amount_traded
-                .partial_cmp(&b.usd_amount_traded)
-                .unwrap()
-        });
+        closed_auctions.sort_by(|a, b| a.exact_usd_amount_traded.cmp(&b.exact_usd_amount_traded));
         closed_auctions.reverse();
         if closed_auctions.len() > number_of_auctions as usize {
             closed_auctions = closed_auctions[0..(number_of_auctions as usize)].to_vec()
@@ -671,6 +1436,38 @@ impl Orderbook {
         }
         Ok(auction_detail_list)
     }
+    pub async fn list_auctions(&self, query: &AuctionListQuery) -> Vec<AuctionDetails> {
+        let auction_details_hashmap = self.auction_details.read().await;
+        let auctions = auction_details_hashmap
+            .values()
+            .filter(|details| query.matches(details))
+            .cloned()
+            .collect();
+        query.apply(auctions)
+    }
+    /// Auctions with a token matching the query, the running ones first and then ordered
+    /// by their interest score.
+    pub async fn search_auctions(&self, query: &str) -> Vec<AuctionDetails> {
+        let auction_ids = self.search_index.read().await.search(query);
+        let auction_details_hashmap = self.auction_details.read().await;
+        let mut auctions: Vec<AuctionDetails> = auction_ids
+            .iter()
+            .filter_map(|auction_id| auction_details_hashmap.get(auction_id))
+            .cloned()
+            .collect();
+        auctions.sort_by(|a, b| {
+            search_rank(a.status)
+                .cmp(&search_rank(b.status))
+                .then(
+                    b.interest_score
+                        .partial_cmp(&a.interest_score)
+                        .unwrap_or(std::cmp::Ordering::Equal),
+                )
+                .then(a.auction_id.cmp(&b.auction_id))
+        });
+        auctions.truncate(MAX_SEARCH_RESULTS);
+        auctions
+    }
     pub async fn get_auction_with_details(&self, auction_id: u64) -> Result<AuctionDetails> {
         let auction_details_hashmap = self.auction_details.read().await;
         match auction_details_hashmap.get(&auction_id) {
@@ -683,25 +1480,134 @@ impl Orderbook {
             }
         }
     }
-    pub async fn update_current_price_of_details(&self, auction_id: u64, price: f64) -> Result<()> {
+    pub async fn get_stats(&self, filter: StatsFilter) -> PlatformStats {
+        let auction_details_hashmap = self.auction_details.read().await;
+        let orders = self.orders.read().await;
+        let auction_participation = self.auction_participation.read().await;
+        let auctions: Vec<&AuctionDetails> = auction_details_hashmap
+            .values()
+            .filter(|details| filter.contains(details.starting_timestamp))
+            .collect();
+
+        let mut stats = PlatformStats {
+            total_auctions: auctions.len() as u64,
+            ..Default::default()
+        };
+        let mut bidding_tokens: HashMap<Address, TokenStats> = HashMap::new();
+        let mut number_of_bids = 0;
+        for details in &auctions {
+            if details.status.is_closed() {
+                stats.closed_auctions += 1;
+            } else {
+                stats.active_auctions += 1;
+            }
+            if details.is_private_auction {
+                stats.private_auctions += 1;
+            }
+            stats.usd_amount_traded = stats
+                .usd_amount_traded
+                .checked_add(&details.exact_usd_amount_traded)
+                .unwrap_or(stats.usd_amount_traded);
+            // open and claimed orders, i.e. all placed orders that were not cancelled
+            number_of_bids += orders
+                .get(&details.auction_id)
+                .map_or(0, AuctionOrders::len);
+            let token_stats = bidding_tokens
+                .entry(details.address_bidding_token)
+                .or_insert_with(|| TokenStats {
+                    address: details.address_bidding_token,
+                    symbol: details.symbol_bidding_token.clone(),
+                    ..Default::default()
+                });
+            token_stats.auctions += 1;
+            token_stats.usd_amount_traded = token_stats
+                .usd_amount_traded
+                .checked_add(&details.exact_usd_amount_traded)
+                .unwrap_or(token_stats.usd_amount_traded);
+        }
+        let auction_ids: HashSet<u64> = auctions.iter().map(|details| details.auction_id).collect();
+        stats.unique_bidders = auction_participation
+            .values()
+            .filter(|participations| !participations.is_disjoint(&auction_ids))
+            .count() as u64;
+        if !auctions.is_empty() {
+            stats.bids_per_auction = number_of_bids as f64 / auctions.len() as f64;
+        }
+        let mut top_bidding_tokens: Vec<TokenStats> = bidding_tokens
+            .into_iter()
+            .map(|(_, token_stats)| token_stats)
+            .collect();
+        top_bidding_tokens.sort_by(|a, b| {
+            b.usd_amount_traded
+                .cmp(&a.usd_amount_traded)
+                .then(b.auctions.cmp(&a.auctions))
+                .then(a.address.cmp(&b.address))
+        });
+        top_bidding_tokens.truncate(NUMBER_OF_TOP_BIDDING_TOKENS);
+        stats.top_bidding_tokens = top_bidding_tokens;
+        stats
+    }
+    pub async fn update_current_price_of_details(
+        &self,
+        auction_id: u64,
+        price_point: PricePoint,
+    ) -> Result<()> {
         let mut auction_details_hashmap = self.auction_details.write().await;
         match auction_details_hashmap.entry(auction_id) {
             Entry::Occupied(mut details) => {
-                details.get_mut().current_clearing_price = price;
+                details.get_mut().current_clearing_price = price_point.price;
+                details.get_mut().exact_current_clearing_price = price_point.exact_price;
             }
             Entry::Vacant(_) => {}
         }
         Ok(())
     }
+    pub async fn update_settlement_of_details(
+        &self,
+        auction_id: u64,
+        settlement: AuctionSettlement,
+    ) {
+        let timestamp = self.status_timestamp().await;
+        let mut auction_details_hashmap = self.auction_details.write().await;
+        match auction_details_hashmap.entry(auction_id) {
+            Entry::Occupied(mut details) => {
+                details.get_mut().settlement = settlement;
+                details.get_mut().status = details.get().status_at(timestamp);
+            }
+            Entry::Vacant(_) => {}
+        }
+    }
+    /// Moves the auctions to their status at the timestamp of the last synced block.
+    /// Timestamp at which the status of the auctions is computed. Until the timestamp of a
+    /// synced block is known, e.g. right after loading the persisted state, the wall-clock
+    /// time is used.
+    async fn status_timestamp(&self) -> u64 {
+        match *self.block_timestamp.read().await {
+            0 => SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            block_timestamp => block_timestamp,
+        }
+    }
+    pub async fn update_block_timestamp(&self, block_timestamp: u64) {
+        *self.block_timestamp.write().await = block_timestamp;
+        for details in self.auction_details.write().await.values_mut() {
+            details.status = details.status_at(block_timestamp);
+        }
+    }
     pub async fn update_current_bidding_amount_of_details(
         &self,
         auction_id: u64,
         amount: U256,
     ) -> Result<()> {
+        let timestamp = self.status_timestamp().await;
         let mut auction_details_hashmap = self.auction_details.write().await;
         match auction_details_hashmap.entry(auction_id) {
             Entry::Occupied(mut details) => {
                 details.get_mut().current_bidding_amount = amount;
+                // the auction is created until its first bid
+                details.get_mut().status = details.get().status_at(timestamp);
             }
             Entry::Vacant(_) => {}
         }
@@ -711,9 +1617,10 @@ impl Orderbook {
         let mut auction_details_hashmap = self.auction_details.write().await;
         match auction_details_hashmap.entry(auction_id) {
             Entry::Occupied(mut details) => {
-                details.get_mut().interest_score = details.get().current_bidding_amount.as_u128()
-                    as f64
-                    / (TEN.pow(details.get().decimals_bidding_token)).as_u128() as f64;
+                // lossy, as the amount and the decimals of the token are not bounded
+                details.get_mut().interest_score =
+                    details.get().current_bidding_amount.to_f64_lossy()
+                        / 10_f64.powi(details.get().decimals_bidding_token.as_u32() as i32);
             }
             Entry::Vacant(_) => {}
         }
@@ -721,47 +1628,47 @@ impl Orderbook {
     }
     pub async fn update_usd_amount_traded_of_details(
         &self,
-        the_graph_reader: &mut UniswapSubgraphClient,
+        price_oracle: &dyn PriceOracle,
         auction_id: u64,
-        chain_id: u32,
     ) -> Result<()> {
         let usd_amount;
         {
             let auction_details_hashmap = self.auction_details.read().await;
             usd_amount = match auction_details_hashmap.get(&auction_id) {
                 Some(details) => {
-                    let current_bidding_amount = details.current_bidding_amount.as_u128() as f64
-                        / TEN.pow(details.decimals_bidding_token).as_u128() as f64;
+                    let current_bidding_amount = Decimal::new(
+                        details.current_bidding_amount,
+                        details.decimals_bidding_token.as_u32(),
+                    );
                     let auctioning_token = details.address_auctioning_token;
                     let bidding_token_address = details.address_bidding_token;
-                    let empty_stable_coin_list: Vec<Address> = Vec::new();
-                    let legit_stable_coins = LEGIT_STABLE_COINS
-                        .get(&chain_id)
-                        .unwrap_or(&empty_stable_coin_list);
-                    let weth = PRICE_FEED_SUPPORTED_TOKENS
-                        .get(&chain_id)
-                        .unwrap_or(&empty_stable_coin_list);
-                    if legit_stable_coins.contains(&bidding_token_address) {
+                    let stable_coins = &self.network.stable_coins;
+                    if stable_coins.contains(&bidding_token_address) {
                         current_bidding_amount
-                    } else if legit_stable_coins.contains(&auctioning_token) {
-                        (current_bidding_amount) / (details.current_clearing_price as f64)
-                    } else if weth.contains(&bidding_token_address) {
-                        let eth_price = the_graph_reader
-                            .get_eth_usd_price(details.end_time_timestamp)
-                            .await?;
-                        current_bidding_amount * eth_price
+                    } else if stable_coins.contains(&auctioning_token) {
+                        current_bidding_amount
+                            .checked_div(&details.exact_current_clearing_price)
+                            .unwrap_or_default()
+                    } else if let Some(price) = price_oracle
+                        .get_usd_price(bidding_token_address, details.end_time_timestamp)
+                        .await?
+                    {
+                        current_bidding_amount
+                            .checked_mul(&price)
+                            .ok_or_else(|| anyhow!("usd amount traded does not fit"))?
                     } else {
-                        0f64
+                        Decimal::zero()
                     }
                 }
-                None => 0f64,
+                None => Decimal::zero(),
             };
         }
         {
             let mut auction_details_hashmap = self.auction_details.write().await;
             match auction_details_hashmap.entry(auction_id) {
                 Entry::Occupied(mut details) => {
-                    details.get_mut().usd_amount_traded = usd_amount;
+                    details.get_mut().usd_amount_traded = usd_amount.to_f64_lossy();
+                    details.get_mut().exact_usd_amount_traded = usd_amount;
                 }
                 Entry::Vacant(_) => {}
             }
@@ -770,9 +1677,260 @@ impl Orderbook {
     }
 }
 
+/// State of the clearing price calculation after accumulating the bids, starting with the
+/// best one, until they cover the auctioned amount or all bids are used.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AccumulatedBids {
+    /// Last accumulated order.
+    pub last_order: Order,
+    /// Sum of the sell amounts of the accumulated orders.
+    pub bid_sum: U256,
+    /// Whether the accumulation stopped at `last_order`, because the bids covered the
+    /// auctioned amount, instead of using all bids.
+    pub covered: bool,
+}
+
+/// Whether the bid sum covers the auctioned amount at the price of the order.
+pub fn covers_auctioned_amount(
+    bid_sum: U256,
+    order: &Order,
+    initial_order: &Order,
+) -> Result<bool> {
+    Ok(bid_sum
+        .checked_mul(order.buy_amount)
+        .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?
+        .ge(&initial_order
+            .sell_amount
+            .checked_mul(order.sell_amount)
+            .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?))
+}
+
+/// Accumulates the sorted orders like the smart contract does for finding the clearing order.
+pub fn accumulate_bids<'a>(
+    orders: impl IntoIterator<Item = &'a Order>,
+    initial_order: Order,
+) -> Result<AccumulatedBids> {
+    let mut accumulated_bids = AccumulatedBids::default();
+    for &order in orders {
+        accumulated_bids.last_order = order;
+        accumulated_bids.bid_sum = accumulated_bids
+            .bid_sum
+            .checked_add(order.sell_amount)
+            .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?;
+        if covers_auctioned_amount(accumulated_bids.bid_sum, &order, &initial_order)? {
+            accumulated_bids.covered = true;
+            break;
+        }
+    }
+    Ok(accumulated_bids)
+}
+
+/// Calculates the clearing order, its volume and the sum of all bids that are considered for
+/// the clearing, given the bids sorted from the best to the worst price.
+pub fn calculate_clearing_order_and_volume(
+    orders: &[Order],
+    initial_order: Order,
+) -> Result<(Order, U256, U256)> {
+    clearing_order_and_volume(accumulate_bids(orders, initial_order)?, initial_order)
+}
+
+/// Calculates the clearing order, its volume and the sum of all bids that are considered for
+/// the clearing from the accumulated bids.
+pub fn clearing_order_and_volume(
+    accumulated_bids: AccumulatedBids,
+    initial_order: Order,
+) -> Result<(Order, U256, U256)> {
+    // code is one to one copy of smart contract, hence no extensive testing
+    let current_bid_sum = accumulated_bids.bid_sum;
+    let current_order = accumulated_bids.last_order;
+    if current_bid_sum.gt(&U256::zero())
+        && current_bid_sum
+            .checked_mul(current_order.buy_amount)
+            .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?
+            .ge(&initial_order
+                .sell_amount
+                .checked_mul(current_order.sell_amount)
+                .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?)
+    {
+        let uncovered_bids = current_bid_sum
+            .checked_sub(
+                initial_order
+                    .sell_amount
+                    .checked_mul(current_order.sell_amount)
+                    .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?
+                    .checked_div(current_order.buy_amount)
+                    .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?,
+            )
+            .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?;
+        if current_order.sell_amount.ge(&uncovered_bids) {
+            let sell_amount_clearing_order = current_order
+                .sell_amount
+                .checked_sub(uncovered_bids)
+                .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?;
+            Ok((
+                current_order,
+                sell_amount_clearing_order,
+                current_bid_sum
+                    .checked_sub(current_order.sell_amount)
+                    .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?
+                    .checked_add(sell_amount_clearing_order)
+                    .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?,
+            ))
+        } else {
+            let clearing_order = Order {
+                sell_amount: current_bid_sum
+                    .checked_sub(current_order.sell_amount)
+                    .unwrap(),
+                buy_amount: initial_order.sell_amount,
+                user_id: 0_u64,
+            };
+            Ok((
+                clearing_order,
+                U256::zero(),
+                current_bid_sum
+                    .checked_sub(current_order.sell_amount)
+                    .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?,
+            ))
+        }
+    } else if current_bid_sum.gt(&initial_order.buy_amount) {
+        let clearing_order = Order {
+            buy_amount: initial_order.sell_amount,
+            sell_amount: current_bid_sum,
+            user_id: 0_u64,
+        };
+        Ok((clearing_order, U256::zero(), current_bid_sum))
+    } else {
+        let clearing_order = Order {
+            buy_amount: initial_order.sell_amount,
+            sell_amount: initial_order.buy_amount,
+            user_id: 0_u64,
+        };
+        let clearing_volume = current_bid_sum
+            .checked_mul(initial_order.sell_amount)
+            .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?
+            .checked_div(initial_order.buy_amount)
+            .ok_or_else(|| anyhow!("error in get_clearing_price_calculation"))?;
+        Ok((clearing_order, clearing_volume, current_bid_sum))
+    }
+}
+
+/// Blocks with events of each auction: its creation, its orders and its clearing. Creations
+/// are only considered for the indexed auctions.
+fn auction_event_blocks(
+    creation_blocks: &[(u64, u64)],
+    new_auction_ids: &[u64],
+    recovered_auctions: &[DeadLetter],
+    order_updates: &OrderUpdates,
+) -> BTreeMap<u64, BTreeSet<u64>> {
+    let creations = creation_blocks
+        .iter()
+        .copied()
+        .filter(|(auction_id, _)| new_auction_ids.contains(auction_id));
+    let recovered_creations = recovered_auctions
+        .iter()
+        .filter(|dead_letter| dead_letter.kind == DeadLetterKind::Creation)
+        .map(|dead_letter| (dead_letter.auction_id, dead_letter.block_number));
+    let orders = order_updates
+        .order_events
+        .iter()
+        .map(|event| (event.auction_id, event.block_number));
+    let clearings = order_updates
+        .auctions_cleared
+        .iter()
+        .map(|clearing| (clearing.auction_id, clearing.settlement.settlement_block));
+    let mut event_blocks: BTreeMap<u64, BTreeSet<u64>> = BTreeMap::new();
+    for (auction_id, block_number) in creations
+        .chain(recovered_creations)
+        .chain(orders)
+        .chain(clearings)
+    {
+        event_blocks
+            .entry(auction_id)
+            .or_default()
+            .insert(block_number);
+    }
+    event_blocks
+}
+
+/// Last persisted checkpoint, whose block is still part of the canonical chain.
+async fn last_canonical_checkpoint(
+    event_reader: &EventReader,
+    database: &Database,
+    chain_id: u32,
+) -> Result<Option<(u64, H256)>> {
+    let checkpoints = database.load_checkpoint_hashes(chain_id).await?;
+    for (block_number, block_hash) in checkpoints.into_iter().rev() {
+        let (canonical_hash, _) = event_reader.get_block_hashes(block_number).await?;
+        if canonical_hash == block_hash {
+            return Ok(Some((block_number, block_hash)));
+        }
+    }
+    Ok(None)
+}
+
+/// Stores the points in the price history of the auctions. A failure only leaves a gap in
+/// the history.
+async fn record_clearing_prices(
+    database: &Database,
+    chain_id: u32,
+    points: &[(u64, ClearingPricePoint)],
+) {
+    if points.is_empty() {
+        return;
+    }
+    if let Err(err) = database.insert_clearing_prices(chain_id, points).await {
+        tracing::warn!(
+            "insert_clearing_prices was not successful with error: {:}",
+            err
+        );
+    }
+}
+
+/// Checks a bid like `placeSellOrders` of the EasyAuction contract at the given time and
+/// returns the position, at which it is inserted into the sorted orders of the auction.
+/// A bid equal to a placed order is not inserted, which the contract skips without reverting.
+pub fn check_bid(
+    details: &AuctionDetails,
+    orders: &[Order],
+    bid: &Order,
+    timestamp: u64,
+) -> Result<Option<usize>> {
+    if timestamp >= details.end_time_timestamp {
+        return Err(anyhow!(
+            "the auction is no longer in the order placement phase"
+        ));
+    }
+    let max_amount = (U256::one() << 96) - U256::one();
+    if bid.sell_amount > max_amount || bid.buy_amount > max_amount {
+        return Err(anyhow!("the amounts of the bid do not fit into uint96"));
+    }
+    if bid.buy_amount.is_zero() {
+        return Err(anyhow!("the buy amount of the bid must be greater than 0"));
+    }
+    if bid.sell_amount <= details.minimum_bidding_amount_per_order {
+        return Err(anyhow!(
+            "the sell amount of the bid must be greater than the minimum bidding amount {}",
+            details.minimum_bidding_amount_per_order
+        ));
+    }
+    let initial_order = details.exact_order;
+    if bid.buy_amount.full_mul(initial_order.buy_amount)
+        >= initial_order.sell_amount.full_mul(bid.sell_amount)
+    {
+        return Err(anyhow!(
+            "the limit price of the bid is not better than the minimal price of the auction"
+        ));
+    }
+    match orders.binary_search(bid) {
+        Ok(_) => Ok(None),
+        Err(position) => Ok(Some(position)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::price_oracle::StaticPriceOracle;
     #[allow(unused_imports)]
     use primitive_types::U256;
 
@@ -820,7 +1978,6 @@ mod tests {
             user_id: 10_u64,
         };
         orderbook.insert_orders(auction_id, vec![order_2]).await;
-        orderbook.sort_orders(auction_id).await;
         assert_eq!(
             orderbook.get_orders(auction_id).await,
             vec![order_2, order_1]
@@ -860,7 +2017,6 @@ mod tests {
         orderbook
             .update_initial_order(auction_id, initial_order)
             .await;
-        orderbook.sort_orders(auction_id).await;
         let result = orderbook
             .get_clearing_order_and_volume(auction_id)
             .await
@@ -908,7 +2064,6 @@ mod tests {
         orderbook
             .update_initial_order(auction_id, initial_order)
             .await;
-        orderbook.sort_orders(auction_id).await;
         let result = orderbook
             .get_clearing_order_and_volume(auction_id)
             .await
@@ -962,4 +2117,571 @@ mod tests {
             *QUEUE_START
         );
     }
+    #[tokio::test(flavor = "current_thread")]
+    async fn reverts_updates_of_reorganized_blocks() {
+        let order_1 = Order {
+            sell_amount: U256::from_dec_str("2").unwrap(),
+            buy_amount: U256::from_dec_str("2").unwrap(),
+            user_id: 10_u64,
+        };
+        let order_2 = Order {
+            sell_amount: U256::from_dec_str("2").unwrap(),
+            buy_amount: U256::from_dec_str("3").unwrap(),
+            user_id: 9_u64,
+        };
+        let auction_id = 1;
+        let event = |kind, order| OrderEvent {
+            kind,
+            auction_id,
+            order,
+            block_number: 0,
+        };
+        let orderbook = Orderbook::new();
+        orderbook
+            .set_auction_details(auction_id, AuctionDetails::default())
+            .await
+            .unwrap();
+        orderbook.insert_orders(auction_id, vec![order_1]).await;
+
+        // events of the blocks 11 and 12, which get reorganized
+        let order_events = vec![
+            event(OrderEventKind::Placed, order_2),
+            event(OrderEventKind::Cancelled, order_1),
+        ];
+        orderbook
+            .apply_order_events(auction_id, &order_events)
+            .await;
+        orderbook
+            .applied_updates
+            .write()
+            .await
+            .push(AppliedUpdates {
+                from_block: 11,
+                order_updates: OrderUpdates {
+                    order_events,
+                    last_block_handled: 12,
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+        assert_eq!(orderbook.get_orders(auction_id).await, vec![order_2]);
+
+        orderbook
+            .revert_updates_after(10, H256::zero(), 1, None)
+            .await
+            .unwrap();
+        assert_eq!(orderbook.get_orders(auction_id).await, vec![order_1]);
+        assert!(orderbook.get_used_auctions(9_u64).await.is_empty());
+        assert!(orderbook.applied_updates.read().await.is_empty());
+        assert!(orderbook.stale_auctions.read().await.contains(&auction_id));
+    }
+    #[test]
+    fn order_state_rejects_impossible_events() {
+        assert_eq!(
+            OrderState::Absent.apply(OrderEventKind::Placed),
+            Some(OrderState::Open)
+        );
+        assert_eq!(OrderState::Open.apply(OrderEventKind::Placed), None);
+        assert_eq!(OrderState::Absent.apply(OrderEventKind::Cancelled), None);
+        assert_eq!(OrderState::Claimed.apply(OrderEventKind::Cancelled), None);
+        assert_eq!(OrderState::Absent.apply(OrderEventKind::Claimed), None);
+        assert_eq!(
+            OrderState::Claimed.revert(OrderEventKind::Claimed),
+            Some(OrderState::Open)
+        );
+        assert_eq!(
+            OrderState::Absent.revert(OrderEventKind::Cancelled),
+            Some(OrderState::Open)
+        );
+        assert_eq!(OrderState::Open.revert(OrderEventKind::Claimed), None);
+    }
+    #[tokio::test(flavor = "current_thread")]
+    async fn applies_order_events_in_on_chain_order() {
+        let order_1 = Order {
+            sell_amount: U256::from_dec_str("2").unwrap(),
+            buy_amount: U256::from_dec_str("2").unwrap(),
+            user_id: 10_u64,
+        };
+        let order_2 = Order {
+            sell_amount: U256::from_dec_str("2").unwrap(),
+            buy_amount: U256::from_dec_str("3").unwrap(),
+            user_id: 9_u64,
+        };
+        let order_3 = Order {
+            sell_amount: U256::from_dec_str("2").unwrap(),
+            buy_amount: U256::from_dec_str("4").unwrap(),
+            user_id: 9_u64,
+        };
+        let auction_id = 1;
+        let event = |kind, order| OrderEvent {
+            kind,
+            auction_id,
+            order,
+            block_number: 0,
+        };
+        let orderbook = Orderbook::new();
+        orderbook
+            .set_auction_details(auction_id, AuctionDetails::default())
+            .await
+            .unwrap();
+        orderbook.insert_orders(auction_id, vec![order_2]).await;
+
+        orderbook
+            .apply_order_events(
+                auction_id,
+                &[
+                    // placed, cancelled and placed again within one range
+                    event(OrderEventKind::Placed, order_1),
+                    event(OrderEventKind::Cancelled, order_1),
+                    event(OrderEventKind::Placed, order_1),
+                    // existing order cancelled and placed again
+                    event(OrderEventKind::Cancelled, order_2),
+                    event(OrderEventKind::Placed, order_2),
+                    // placed and claimed
+                    event(OrderEventKind::Placed, order_3),
+                    event(OrderEventKind::Claimed, order_3),
+                    // claimed orders can not be cancelled
+                    event(OrderEventKind::Cancelled, order_3),
+                ],
+            )
+            .await;
+        let mut expected_orders = vec![order_1, order_2, order_3];
+        expected_orders.sort();
+        assert_eq!(orderbook.get_orders(auction_id).await, expected_orders);
+        let orders_without_claimed: Vec<Order> = orderbook.orders.read().await[&auction_id]
+            .open_orders()
+            .copied()
+            .collect();
+        let mut expected_orders = vec![order_1, order_2];
+        expected_orders.sort();
+        assert_eq!(orders_without_claimed, expected_orders);
+    }
+    #[tokio::test(flavor = "current_thread")]
+    async fn reverts_interleaved_order_events() {
+        let order_1 = Order {
+            sell_amount: U256::from_dec_str("2").unwrap(),
+            buy_amount: U256::from_dec_str("2").unwrap(),
+            user_id: 10_u64,
+        };
+        let order_2 = Order {
+            sell_amount: U256::from_dec_str("2").unwrap(),
+            buy_amount: U256::from_dec_str("3").unwrap(),
+            user_id: 9_u64,
+        };
+        let auction_id = 1;
+        let event = |kind, order| OrderEvent {
+            kind,
+            auction_id,
+            order,
+            block_number: 0,
+        };
+        let orderbook = Orderbook::new();
+        orderbook
+            .set_auction_details(auction_id, AuctionDetails::default())
+            .await
+            .unwrap();
+        orderbook.insert_orders(auction_id, vec![order_2]).await;
+        let order_events = vec![
+            event(OrderEventKind::Cancelled, order_2),
+            event(OrderEventKind::Placed, order_1),
+            event(OrderEventKind::Cancelled, order_1),
+            event(OrderEventKind::Placed, order_2),
+            event(OrderEventKind::Claimed, order_2),
+        ];
+        orderbook
+            .apply_order_events(auction_id, &order_events)
+            .await;
+        assert_eq!(orderbook.get_orders(auction_id).await, vec![order_2]);
+        assert_eq!(
+            orderbook.order_state(auction_id, &order_2).await,
+            OrderState::Claimed
+        );
+
+        let removed_orders = orderbook
+            .revert_order_events(auction_id, &order_events)
+            .await;
+        assert!(removed_orders.is_empty());
+        assert_eq!(orderbook.get_orders(auction_id).await, vec![order_2]);
+        assert_eq!(
+            orderbook.order_state(auction_id, &order_2).await,
+            OrderState::Open
+        );
+        assert_eq!(
+            orderbook.order_state(auction_id, &order_1).await,
+            OrderState::Absent
+        );
+    }
+    #[tokio::test(flavor = "current_thread")]
+    async fn get_clearing_order_and_volume_of_settled_auction() {
+        let order = Order {
+            sell_amount: U256::from_dec_str("2").unwrap(),
+            buy_amount: U256::from_dec_str("2").unwrap(),
+            user_id: 1_u64,
+        };
+        let initial_order = Order {
+            sell_amount: U256::from_dec_str("4").unwrap(),
+            buy_amount: U256::from_dec_str("2").unwrap(),
+            user_id: 10_u64,
+        };
+        let clearing_price_order = Order {
+            sell_amount: U256::from_dec_str("3").unwrap(),
+            buy_amount: U256::from_dec_str("4").unwrap(),
+            user_id: 0_u64,
+        };
+        let auction_id = 1;
+        let mut orderbook = Orderbook::new();
+        orderbook
+            .set_auction_details(auction_id, AuctionDetails::default())
+            .await
+            .unwrap();
+        orderbook.insert_orders(auction_id, vec![order]).await;
+        orderbook
+            .update_initial_order(auction_id, initial_order)
+            .await;
+        orderbook
+            .update_settlement_of_details(
+                auction_id,
+                AuctionSettlement {
+                    is_settled: true,
+                    clearing_price_order,
+                    sold_bidding_tokens: U256::from_dec_str("3").unwrap(),
+                    ..Default::default()
+                },
+            )
+            .await;
+        let result = orderbook
+            .get_clearing_order_and_volume(auction_id)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result,
+            (
+                clearing_price_order,
+                U256::zero(),
+                U256::from_dec_str("3").unwrap()
+            )
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn records_a_clearing_price_point_per_block_with_events() {
+        let auction_id = 1;
+        let orderbook = Orderbook::new();
+        orderbook
+            .set_auction_details(
+                auction_id,
+                AuctionDetails {
+                    settlement: AuctionSettlement {
+                        is_settled: true,
+                        settlement_block: 7,
+                        sold_bidding_tokens: U256::from(4),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        orderbook
+            .update_initial_order(
+                auction_id,
+                Order {
+                    sell_amount: U256::from(100),
+                    buy_amount: U256::from(1),
+                    user_id: 0,
+                },
+            )
+            .await;
+        let placed = |sell_amount: u64, user_id, block_number| OrderEvent {
+            kind: OrderEventKind::Placed,
+            auction_id,
+            order: Order {
+                sell_amount: U256::from(sell_amount),
+                buy_amount: U256::from(1),
+                user_id,
+            },
+            block_number,
+        };
+        let order_events = vec![placed(2, 1, 5), placed(3, 2, 7), placed(4, 3, 9)];
+        let blocks: BTreeSet<u64> = vec![5, 7, 9].into_iter().collect();
+        let block_timestamps: BTreeMap<u64, u64> = vec![(5, 50), (7, 70)].into_iter().collect();
+
+        let points = orderbook
+            .apply_order_events_by_block(auction_id, &order_events, &blocks, &block_timestamps)
+            .await;
+
+        // Before its settlement block, the clearing of the auction follows its orders.
+        assert_eq!(
+            points
+                .iter()
+                .map(|point| (point.block_number, point.timestamp, point.bidding_amount))
+                .collect::<Vec<_>>(),
+            vec![
+                (5, 50, Decimal::new(U256::from(2), 0)),
+                (7, 70, Decimal::new(U256::from(4), 0)),
+            ]
+        );
+        assert_eq!(orderbook.get_orders(auction_id).await.len(), 3);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    #[ignore]
+    async fn postgres_loads_statuses_at_the_current_time() {
+        let db = Database::new("postgresql://").unwrap();
+        db.clear().await.unwrap();
+        let chain_id = 4u32;
+        let auction = |auction_id, end_time_timestamp| AuctionDetails {
+            auction_id,
+            starting_timestamp: 100,
+            order_cancellation_end_date: end_time_timestamp,
+            end_time_timestamp,
+            current_bidding_amount: U256::from(1),
+            ..Default::default()
+        };
+        db.persist_updates(
+            chain_id,
+            &[auction(1, u64::MAX), auction(2, 200)],
+            &AppliedUpdates {
+                block_hash: H256::from_low_u64_be(12),
+                new_auctions: vec![1, 2],
+                order_updates: OrderUpdates {
+                    last_block_handled: 12,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let orderbook = Orderbook::new();
+        orderbook.load_from_database(&db, chain_id).await.unwrap();
+        // No block range is synced yet, hence the statuses are computed at the current time.
+        assert_eq!(
+            orderbook.get_auction_with_details(1).await.unwrap().status,
+            AuctionStatus::OpenWithCancellation
+        );
+        assert_eq!(
+            orderbook.get_auction_with_details(2).await.unwrap().status,
+            AuctionStatus::Ended
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn aggregates_stats_of_auctions() {
+        let dai = Address::from_low_u64_be(1);
+        let weth = Address::from_low_u64_be(2);
+        let auction =
+            |auction_id, starting_timestamp, end_time_timestamp, token, usd: &str| AuctionDetails {
+                auction_id,
+                starting_timestamp,
+                end_time_timestamp,
+                address_bidding_token: token,
+                symbol_bidding_token: if token == dai { "DAI" } else { "WETH" }.to_string(),
+                exact_usd_amount_traded: usd.parse().unwrap(),
+                ..Default::default()
+            };
+        let order = |sell_amount: u64, user_id| Order {
+            sell_amount: U256::from(sell_amount),
+            buy_amount: U256::from(1),
+            user_id,
+        };
+        let orderbook = Orderbook::new();
+        *orderbook.block_timestamp.write().await = 1000;
+        let auctions = vec![
+            AuctionDetails {
+                is_private_auction: true,
+                ..auction(1, 100, 0, dai, "10")
+            },
+            auction(2, 200, u64::MAX, dai, "5"),
+            auction(3, 300, 0, weth, "20"),
+        ];
+        for details in auctions {
+            orderbook
+                .set_auction_details(details.auction_id, details)
+                .await
+                .unwrap();
+        }
+        orderbook
+            .insert_orders(1, vec![order(1, 1), order(2, 2)])
+            .await;
+        orderbook.insert_orders(2, vec![order(3, 2)]).await;
+        // claimed bids are still counted
+        orderbook.remove_claimed_orders(1, vec![order(1, 1)]).await;
+
+        let stats = orderbook.get_stats(StatsFilter::default()).await;
+        assert_eq!(stats.total_auctions, 3);
+        assert_eq!(stats.active_auctions, 1);
+        assert_eq!(stats.closed_auctions, 2);
+        assert_eq!(stats.private_auctions, 1);
+        assert_eq!(stats.usd_amount_traded, "35".parse().unwrap());
+        assert_eq!(stats.unique_bidders, 2);
+        assert!((stats.bids_per_auction - 1.0).abs() < f64::EPSILON);
+        assert_eq!(
+            stats.top_bidding_tokens,
+            vec![
+                TokenStats {
+                    address: weth,
+                    symbol: "WETH".to_string(),
+                    auctions: 1,
+                    usd_amount_traded: "20".parse().unwrap(),
+                },
+                TokenStats {
+                    address: dai,
+                    symbol: "DAI".to_string(),
+                    auctions: 2,
+                    usd_amount_traded: "15".parse().unwrap(),
+                },
+            ]
+        );
+
+        let stats = orderbook
+            .get_stats(StatsFilter {
+                from_timestamp: Some(150),
+                to_timestamp: Some(250),
+            })
+            .await;
+        assert_eq!(stats.total_auctions, 1);
+        assert_eq!(stats.active_auctions, 1);
+        assert_eq!(stats.closed_auctions, 0);
+        assert_eq!(stats.usd_amount_traded, "5".parse().unwrap());
+        assert_eq!(stats.unique_bidders, 1);
+        assert_eq!(stats.top_bidding_tokens.len(), 1);
+    }
+
+    #[test]
+    fn rejects_bids_that_the_contract_rejects() {
+        let order = |sell_amount: u128, buy_amount: u128, user_id| Order {
+            sell_amount: U256::from(sell_amount),
+            buy_amount: U256::from(buy_amount),
+            user_id,
+        };
+        let details = AuctionDetails {
+            exact_order: order(1000, 100, 1),
+            minimum_bidding_amount_per_order: U256::from(10),
+            end_time_timestamp: 300,
+            ..Default::default()
+        };
+        let placed_bid = order(20, 150, 2);
+        let orders = vec![placed_bid];
+        let check = |bid: Order, now| check_bid(&details, &orders, &bid, now);
+        let rejection = |bid: Order, now| check(bid, now).unwrap_err().to_string();
+        let too_large = U256::one() << 96;
+
+        assert_eq!(check(order(20, 100, 2), 200).unwrap(), Some(0));
+        assert_eq!(check(order(20, 180, 2), 200).unwrap(), Some(1));
+        assert!(rejection(order(20, 100, 2), 300).contains("order placement phase"));
+        let bid = Order {
+            sell_amount: too_large,
+            ..order(0, 100, 2)
+        };
+        assert!(rejection(bid, 200).contains("uint96"));
+        let bid = Order {
+            buy_amount: too_large,
+            ..order(20, 0, 2)
+        };
+        assert!(rejection(bid, 200).contains("uint96"));
+        assert!(rejection(order(20, 0, 2), 200).contains("greater than 0"));
+        assert!(rejection(order(0, 100, 2), 200).contains("minimum bidding amount"));
+        assert!(rejection(order(10, 50, 2), 200).contains("minimum bidding amount"));
+        assert!(rejection(order(20, 200, 2), 200).contains("limit price"));
+        assert_eq!(check(placed_bid, 200).unwrap(), None);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn updates_status_with_block_timestamp_and_settlement() {
+        let auction_id = 1;
+        let mut orderbook = Orderbook::new();
+        orderbook.update_block_timestamp(150).await;
+        orderbook
+            .set_auction_details(
+                auction_id,
+                AuctionDetails {
+                    auction_id,
+                    starting_timestamp: 100,
+                    order_cancellation_end_date: 200,
+                    end_time_timestamp: 300,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        orderbook
+            .update_initial_order(
+                auction_id,
+                Order {
+                    sell_amount: U256::from(100),
+                    buy_amount: U256::from(1),
+                    user_id: 0,
+                },
+            )
+            .await;
+        let orderbook = &orderbook;
+        let status = move || async move {
+            orderbook
+                .get_auction_with_details(auction_id)
+                .await
+                .unwrap()
+                .status
+        };
+        // the auction is created until its first bid is indexed
+        assert_eq!(status().await, AuctionStatus::Created);
+        let bid = OrderEvent {
+            kind: OrderEventKind::Placed,
+            auction_id,
+            order: Order {
+                sell_amount: U256::from(2),
+                buy_amount: U256::from(1),
+                user_id: 1,
+            },
+            block_number: 0,
+        };
+        orderbook.apply_order_events(auction_id, &[bid]).await;
+        orderbook
+            .update_clearing_price_info(&StaticPriceOracle::new(HashMap::new()), auction_id)
+            .await
+            .unwrap();
+        assert_eq!(status().await, AuctionStatus::OpenWithCancellation);
+        assert_eq!(
+            orderbook
+                .get_most_interesting_auctions(10)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        orderbook.update_block_timestamp(350).await;
+        assert_eq!(status().await, AuctionStatus::Ended);
+        assert!(orderbook
+            .get_most_interesting_auctions(10)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            orderbook
+                .get_most_interesting_closed_auctions(10)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        orderbook
+            .update_settlement_of_details(
+                auction_id,
+                AuctionSettlement {
+                    is_settled: true,
+                    is_atomically_closed: true,
+                    ..Default::default()
+                },
+            )
+            .await;
+        assert_eq!(status().await, AuctionStatus::AtomicallyClosed);
+        orderbook
+            .update_settlement_of_details(auction_id, AuctionSettlement::default())
+            .await;
+        assert_eq!(status().await, AuctionStatus::Ended);
+    }
 }
@@ -0,0 +1,227 @@
+//! Sources of USD prices, which are used to compute the USD volume of auctions.
+//!
+//! Which oracles are used for a network is part of its network configuration. If several
+//! oracles are configured, the first one knowing the token is used. Auctions are valued at
+//! the price of the day they end, which is cached in the database.
+
+mod cache;
+mod static_prices;
+mod uniswap_pool;
+
+pub use cache::{is_day_over, CachedPriceOracle};
+pub use static_prices::StaticPriceOracle;
+pub use uniswap_pool::{PoolVersion, UniswapPoolOracle};
+
+use crate::subgraph::uniswap_graph_api::UniswapSubgraphClient;
+use anyhow::{Context, Result};
+use ethcontract::Address;
+use futures::future::{BoxFuture, FutureExt};
+use model::decimal::Decimal;
+use serde::Deserialize;
+use std::path::PathBuf;
+use web3::Web3;
+
+pub trait PriceOracle: Send + Sync {
+    /// USD price of one whole token at the given timestamp, or None if the oracle can not
+    /// price the token. Oracles reading on-chain state return the current price.
+    fn get_usd_price(
+        &self,
+        token: Address,
+        timestamp: u64,
+    ) -> BoxFuture<'_, Result<Option<Decimal>>>;
+
+    /// Whether the prices of the oracle are the prices at the given timestamp. Oracles
+    /// reading on-chain state or fixed prices are not historical.
+    fn is_historical(&self) -> bool {
+        false
+    }
+
+    /// Like `get_usd_price`, but also returns whether the price is historical, i.e. whether
+    /// it can be stored as the price at the timestamp.
+    fn get_dated_usd_price(
+        &self,
+        token: Address,
+        timestamp: u64,
+    ) -> BoxFuture<'_, Result<Option<(Decimal, bool)>>> {
+        async move {
+            let price = self.get_usd_price(token, timestamp).await?;
+            Ok(price.map(|price| (price, self.is_historical())))
+        }
+        .boxed()
+    }
+}
+
+/// Configuration of a price oracle in the network configuration.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum PriceOracleConfig {
+    /// Daily reserves of a pair indexed by a Uniswap V2 subgraph `org/name`.
+    #[serde(rename_all = "camelCase")]
+    UniswapV2Subgraph {
+        subgraph: String,
+        token: Address,
+        pair: Address,
+    },
+    /// Current reserves of an on-chain Uniswap V2 pair.
+    #[serde(rename_all = "camelCase")]
+    UniswapV2Pool { token: Address, pool: Address },
+    /// Current price of an on-chain Uniswap V3 pool.
+    #[serde(rename_all = "camelCase")]
+    UniswapV3Pool { token: Address, pool: Address },
+    /// JSON file mapping token addresses to decimal USD prices.
+    #[serde(rename_all = "camelCase")]
+    StaticPrices { path: PathBuf },
+}
+
+/// Price oracles, which are asked in order until one of them knows the token.
+#[derive(Default)]
+pub struct PriceOracles {
+    oracles: Vec<Box<dyn PriceOracle>>,
+}
+
+impl PriceOracles {
+    pub fn new(oracles: Vec<Box<dyn PriceOracle>>) -> Self {
+        Self { oracles }
+    }
+
+    pub fn from_configs(
+        configs: &[PriceOracleConfig],
+        web3: &Web3<web3::transports::Http>,
+    ) -> Result<Self> {
+        let oracles = configs
+            .iter()
+            .map(|config| -> Result<Box<dyn PriceOracle>> {
+                Ok(match config {
+                    PriceOracleConfig::UniswapV2Subgraph {
+                        subgraph,
+                        token,
+                        pair,
+                    } => {
+                        let (org, name) = subgraph
+                            .split_once('/')
+                            .with_context(|| format!("invalid subgraph name {}", subgraph))?;
+                        Box::new(UniswapSubgraphClient::new(org, name, *token, *pair)?)
+                    }
+                    PriceOracleConfig::UniswapV2Pool { token, pool } => {
+                        Box::new(UniswapPoolOracle::new(web3, PoolVersion::V2, *token, *pool))
+                    }
+                    PriceOracleConfig::UniswapV3Pool { token, pool } => {
+                        Box::new(UniswapPoolOracle::new(web3, PoolVersion::V3, *token, *pool))
+                    }
+                    PriceOracleConfig::StaticPrices { path } => {
+                        Box::new(StaticPriceOracle::from_file(path)?)
+                    }
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self::new(oracles))
+    }
+}
+
+impl PriceOracle for PriceOracles {
+    fn get_usd_price(
+        &self,
+        token: Address,
+        timestamp: u64,
+    ) -> BoxFuture<'_, Result<Option<Decimal>>> {
+        async move {
+            let price = self.get_dated_usd_price(token, timestamp).await?;
+            Ok(price.map(|(price, _)| price))
+        }
+        .boxed()
+    }
+
+    fn is_historical(&self) -> bool {
+        self.oracles.iter().all(|oracle| oracle.is_historical())
+    }
+
+    fn get_dated_usd_price(
+        &self,
+        token: Address,
+        timestamp: u64,
+    ) -> BoxFuture<'_, Result<Option<(Decimal, bool)>>> {
+        async move {
+            for oracle in &self.oracles {
+                if let Some(price) = oracle.get_dated_usd_price(token, timestamp).await? {
+                    return Ok(Some(price));
+                }
+            }
+            Ok(None)
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use maplit::hashmap;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    #[tokio::test]
+    async fn first_oracle_knowing_the_token_is_used() {
+        let token_a = Address::from_low_u64_be(1);
+        let token_b = Address::from_low_u64_be(2);
+        let oracles = PriceOracles::new(vec![
+            Box::new(StaticPriceOracle::new(hashmap! {
+                token_a => Decimal::from_str("2").unwrap(),
+            })),
+            Box::new(StaticPriceOracle::new(hashmap! {
+                token_a => Decimal::from_str("3").unwrap(),
+                token_b => Decimal::from_str("4").unwrap(),
+            })),
+        ]);
+        assert_eq!(
+            oracles.get_usd_price(token_a, 0).await.unwrap(),
+            Some(Decimal::from_str("2").unwrap())
+        );
+        assert_eq!(
+            oracles.get_usd_price(token_b, 0).await.unwrap(),
+            Some(Decimal::from_str("4").unwrap())
+        );
+        assert_eq!(
+            oracles
+                .get_usd_price(Address::from_low_u64_be(3), 0)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn deserializes_configs() {
+        let configs: Vec<PriceOracleConfig> = serde_json::from_value(json!([
+            {
+                "kind": "uniswapV2Subgraph",
+                "subgraph": "uniswap/uniswap-v2",
+                "token": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                "pair": "0xb4e16d0168e52d35cacd2c6185b44281ec28c9dc"
+            },
+            {
+                "kind": "uniswapV3Pool",
+                "token": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                "pool": "0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640"
+            },
+            {
+                "kind": "staticPrices",
+                "path": "prices.json"
+            }
+        ]))
+        .unwrap();
+        assert_eq!(
+            configs[2],
+            PriceOracleConfig::StaticPrices {
+                path: PathBuf::from("prices.json")
+            }
+        );
+        assert!(matches!(
+            configs[0],
+            PriceOracleConfig::UniswapV2Subgraph { .. }
+        ));
+        assert!(matches!(
+            configs[1],
+            PriceOracleConfig::UniswapV3Pool { .. }
+        ));
+    }
+}
@@ -0,0 +1,226 @@
+use super::PriceOracle;
+use crate::database::Database;
+use anyhow::Result;
+use ethcontract::Address;
+use futures::future::{BoxFuture, FutureExt};
+use model::decimal::Decimal;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Stores the daily prices of an oracle in the database, such that the values of past
+/// auctions do not change anymore and survive restarts.
+///
+/// Historical prices are only stored for days that are over, as the price of the current day
+/// might still change. Prices of oracles that only know the current price are only stored for
+/// the current day, and are updated until the day is over.
+pub struct CachedPriceOracle<O> {
+    inner: O,
+    database: Database,
+    chain_id: u32,
+}
+
+impl<O: PriceOracle> CachedPriceOracle<O> {
+    pub fn new(inner: O, database: Database, chain_id: u32) -> Self {
+        Self {
+            inner,
+            database,
+            chain_id,
+        }
+    }
+
+    /// Stores the price of the day of the timestamp, if it is not yet stored and the oracle
+    /// knows the historical price. Returns whether a new price was stored.
+    pub async fn backfill(&self, token: Address, timestamp: u64) -> Result<bool> {
+        let day = start_of_day(timestamp);
+        if !is_over(day)
+            || self
+                .database
+                .get_token_price(self.chain_id, token, day)
+                .await?
+                .is_some()
+        {
+            return Ok(false);
+        }
+        match self.inner.get_dated_usd_price(token, timestamp).await? {
+            Some((price, true)) => {
+                self.database
+                    .insert_token_price(self.chain_id, token, day, price)
+                    .await?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+impl<O: PriceOracle> PriceOracle for CachedPriceOracle<O> {
+    fn get_usd_price(
+        &self,
+        token: Address,
+        timestamp: u64,
+    ) -> BoxFuture<'_, Result<Option<Decimal>>> {
+        async move {
+            let day = start_of_day(timestamp);
+            let today = start_of_day(now());
+            if day < today {
+                if let Some(price) = self
+                    .database
+                    .get_token_price(self.chain_id, token, day)
+                    .await?
+                {
+                    return Ok(Some(price));
+                }
+            }
+            let price = self.inner.get_dated_usd_price(token, timestamp).await?;
+            if let Some((price, is_historical)) = price {
+                if is_storable(day, today, is_historical) {
+                    self.database
+                        .insert_token_price(self.chain_id, token, day, price)
+                        .await?;
+                }
+            }
+            Ok(price.map(|(price, _)| price))
+        }
+        .boxed()
+    }
+}
+
+/// Whether a price of the day can be stored as its final price. Historical prices are final
+/// once the day is over, while the current price is only the price of the current day.
+fn is_storable(day: u64, today: u64, is_historical: bool) -> bool {
+    if is_historical {
+        day < today
+    } else {
+        day == today
+    }
+}
+
+/// Whether the day of the timestamp is over, such that its price does not change anymore.
+pub fn is_day_over(timestamp: u64) -> bool {
+    is_over(start_of_day(timestamp))
+}
+
+fn start_of_day(timestamp: u64) -> u64 {
+    timestamp - timestamp % SECONDS_PER_DAY
+}
+
+fn is_over(day: u64) -> bool {
+    day + SECONDS_PER_DAY <= now()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before unix epoch")
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price_oracle::StaticPriceOracle;
+    use maplit::hashmap;
+    use std::str::FromStr;
+
+    /// Static prices pretending to be the prices of any day.
+    struct HistoricalPriceOracle(StaticPriceOracle);
+
+    impl PriceOracle for HistoricalPriceOracle {
+        fn get_usd_price(
+            &self,
+            token: Address,
+            timestamp: u64,
+        ) -> BoxFuture<'_, Result<Option<Decimal>>> {
+            self.0.get_usd_price(token, timestamp)
+        }
+
+        fn is_historical(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn days_start_at_midnight_utc() {
+        assert_eq!(start_of_day(1625260016), 1625184000);
+        assert_eq!(start_of_day(1625184000), 1625184000);
+        assert!(is_over(1625184000));
+        assert!(!is_over(start_of_day(u64::MAX - SECONDS_PER_DAY)));
+    }
+
+    #[test]
+    fn only_stores_current_prices_for_the_current_day() {
+        let today = 1625184000;
+        let yesterday = today - SECONDS_PER_DAY;
+        let tomorrow = today + SECONDS_PER_DAY;
+        assert!(!is_storable(yesterday, today, false));
+        assert!(is_storable(today, today, false));
+        assert!(!is_storable(tomorrow, today, false));
+        assert!(is_storable(yesterday, today, true));
+        assert!(!is_storable(today, today, true));
+        assert!(!is_storable(tomorrow, today, true));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    #[ignore]
+    async fn postgres_caches_prices_of_past_days() {
+        let db = Database::new("postgresql://").unwrap();
+        db.clear().await.unwrap();
+        let token = Address::from_low_u64_be(1);
+        let price = Decimal::from_str("2").unwrap();
+        let oracle = CachedPriceOracle::new(
+            HistoricalPriceOracle(StaticPriceOracle::new(hashmap! { token => price })),
+            db.clone(),
+            1,
+        );
+        assert_eq!(
+            oracle.get_usd_price(token, 1625260016).await.unwrap(),
+            Some(price)
+        );
+        assert_eq!(
+            db.get_token_price(1, token, 1625184000).await.unwrap(),
+            Some(price)
+        );
+
+        // the cached price is used, even if the oracle knows a different price by now
+        let oracle = CachedPriceOracle::new(StaticPriceOracle::new(hashmap! {}), db.clone(), 1);
+        assert_eq!(
+            oracle.get_usd_price(token, 1625200000).await.unwrap(),
+            Some(price)
+        );
+        assert!(!oracle.backfill(token, 1625200000).await.unwrap());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    #[ignore]
+    async fn postgres_does_not_cache_current_prices_for_past_days() {
+        let db = Database::new("postgresql://").unwrap();
+        db.clear().await.unwrap();
+        let token = Address::from_low_u64_be(1);
+        let price = Decimal::from_str("2").unwrap();
+        let oracle = CachedPriceOracle::new(
+            StaticPriceOracle::new(hashmap! { token => price }),
+            db.clone(),
+            1,
+        );
+        assert_eq!(
+            oracle.get_usd_price(token, 1625260016).await.unwrap(),
+            Some(price)
+        );
+        assert!(!oracle.backfill(token, 1625260016).await.unwrap());
+        assert_eq!(
+            db.get_token_price(1, token, 1625184000).await.unwrap(),
+            None
+        );
+
+        let today = start_of_day(now());
+        assert_eq!(
+            oracle.get_usd_price(token, today).await.unwrap(),
+            Some(price)
+        );
+        assert_eq!(
+            db.get_token_price(1, token, today).await.unwrap(),
+            Some(price)
+        );
+    }
+}
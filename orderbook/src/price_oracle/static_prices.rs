@@ -0,0 +1,67 @@
+use super::PriceOracle;
+use anyhow::{Context, Result};
+use ethcontract::Address;
+use futures::future::{BoxFuture, FutureExt};
+use model::decimal::Decimal;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Fixed USD prices, e.g. for tokens without a liquid pool or for test networks.
+pub struct StaticPriceOracle {
+    prices: HashMap<Address, Decimal>,
+}
+
+impl StaticPriceOracle {
+    pub fn new(prices: HashMap<Address, Decimal>) -> Self {
+        Self { prices }
+    }
+
+    /// Reads the prices from a JSON object mapping token addresses to decimal strings, e.g.
+    /// `{ "0x6b17…1d0f": "1.0" }`.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let prices = fs::read_to_string(path)
+            .with_context(|| format!("reading price file {} failed", path.display()))?;
+        Self::parse(&prices)
+            .with_context(|| format!("parsing price file {} failed", path.display()))
+    }
+
+    pub fn parse(prices: &str) -> Result<Self> {
+        Ok(Self::new(serde_json::from_str(prices)?))
+    }
+}
+
+impl PriceOracle for StaticPriceOracle {
+    fn get_usd_price(
+        &self,
+        token: Address,
+        _timestamp: u64,
+    ) -> BoxFuture<'_, Result<Option<Decimal>>> {
+        futures::future::ready(Ok(self.prices.get(&token).copied())).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    #[test]
+    fn parses_price_file() {
+        let oracle = StaticPriceOracle::parse(
+            &json!({
+                "0x6b175474e89094c44da98b954eedeac495271d0f": "1",
+                "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2": "2345.678",
+            })
+            .to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            oracle.prices[&Address::from_str("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap()],
+            Decimal::from_str("2345.678").unwrap()
+        );
+        assert_eq!(oracle.prices.len(), 2);
+        assert!(StaticPriceOracle::parse(r#"{"0x01": 1.5}"#).is_err());
+    }
+}
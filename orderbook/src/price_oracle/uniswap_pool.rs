@@ -0,0 +1,197 @@
+use super::PriceOracle;
+use anyhow::{anyhow, bail, Result};
+use contracts::{UniswapV2Pair, UniswapV3Pool, ERC20};
+use ethcontract::Address;
+use futures::future::{BoxFuture, FutureExt};
+use model::decimal::Decimal;
+use primitive_types::{U256, U512};
+use std::sync::Mutex;
+use web3::Web3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PoolVersion {
+    V2,
+    V3,
+}
+
+/// Reads the current price of a token from an on-chain Uniswap pool, in which the token is
+/// traded against a USD stable coin.
+pub struct UniswapPoolOracle {
+    web3: Web3<web3::transports::Http>,
+    version: PoolVersion,
+    token: Address,
+    pool: Address,
+    pool_tokens: Mutex<Option<PoolTokens>>,
+}
+
+/// Immutable properties of the pool, which are read only once.
+#[derive(Clone, Copy, Debug)]
+struct PoolTokens {
+    token_is_token0: bool,
+    decimals0: u8,
+    decimals1: u8,
+}
+
+impl UniswapPoolOracle {
+    pub fn new(
+        web3: &Web3<web3::transports::Http>,
+        version: PoolVersion,
+        token: Address,
+        pool: Address,
+    ) -> Self {
+        Self {
+            web3: web3.clone(),
+            version,
+            token,
+            pool,
+            pool_tokens: Mutex::new(None),
+        }
+    }
+
+    async fn pool_tokens(&self) -> Result<PoolTokens> {
+        let cached = *self.pool_tokens.lock().unwrap();
+        if let Some(pool_tokens) = cached {
+            return Ok(pool_tokens);
+        }
+        let (token0, token1) = match self.version {
+            PoolVersion::V2 => {
+                let pair = UniswapV2Pair::at(&self.web3, self.pool);
+                (pair.token0().call().await?, pair.token1().call().await?)
+            }
+            PoolVersion::V3 => {
+                let pool = UniswapV3Pool::at(&self.web3, self.pool);
+                (pool.token0().call().await?, pool.token1().call().await?)
+            }
+        };
+        if self.token != token0 && self.token != token1 {
+            bail!(
+                "token {:?} is not traded in pool {:?}",
+                self.token,
+                self.pool
+            );
+        }
+        let pool_tokens = PoolTokens {
+            token_is_token0: self.token == token0,
+            decimals0: ERC20::at(&self.web3, token0).decimals().call().await?,
+            decimals1: ERC20::at(&self.web3, token1).decimals().call().await?,
+        };
+        *self.pool_tokens.lock().unwrap() = Some(pool_tokens);
+        Ok(pool_tokens)
+    }
+
+    async fn current_price(&self) -> Result<Decimal> {
+        let pool_tokens = self.pool_tokens().await?;
+        let price_of_token0 = match self.version {
+            PoolVersion::V2 => {
+                let (reserve0, reserve1, _) = UniswapV2Pair::at(&self.web3, self.pool)
+                    .get_reserves()
+                    .call()
+                    .await?;
+                price_from_reserves(
+                    U256::from(reserve0),
+                    U256::from(reserve1),
+                    pool_tokens.decimals0,
+                    pool_tokens.decimals1,
+                )
+            }
+            PoolVersion::V3 => {
+                let (sqrt_price_x96, ..) = UniswapV3Pool::at(&self.web3, self.pool)
+                    .slot0()
+                    .call()
+                    .await?;
+                price_from_sqrt_price_x96(
+                    sqrt_price_x96,
+                    pool_tokens.decimals0,
+                    pool_tokens.decimals1,
+                )
+            }
+        }
+        .ok_or_else(|| anyhow!("pool {:?} has no valid price", self.pool))?;
+        if pool_tokens.token_is_token0 {
+            Ok(price_of_token0)
+        } else {
+            Decimal::new(U256::one(), 0)
+                .checked_div(&price_of_token0)
+                .ok_or_else(|| anyhow!("pool {:?} has no valid price", self.pool))
+        }
+    }
+}
+
+impl PriceOracle for UniswapPoolOracle {
+    fn get_usd_price(
+        &self,
+        token: Address,
+        _timestamp: u64,
+    ) -> BoxFuture<'_, Result<Option<Decimal>>> {
+        async move {
+            if token != self.token {
+                return Ok(None);
+            }
+            Ok(Some(self.current_price().await?))
+        }
+        .boxed()
+    }
+}
+
+/// Price of one whole token0 in token1 of a Uniswap V2 pair.
+fn price_from_reserves(
+    reserve0: U256,
+    reserve1: U256,
+    decimals0: u8,
+    decimals1: u8,
+) -> Option<Decimal> {
+    Decimal::new(reserve1, decimals1 as u32).checked_div(&Decimal::new(reserve0, decimals0 as u32))
+}
+
+/// Price of one whole token0 in token1 of a Uniswap V3 pool, given the square root of the
+/// atomic price as Q64.96 number.
+fn price_from_sqrt_price_x96(
+    sqrt_price_x96: U256,
+    decimals0: u8,
+    decimals1: u8,
+) -> Option<Decimal> {
+    // sqrt_price_x96 has at most 160 bits, hence its square fits into U512
+    let atomic_price =
+        Decimal::from_ratio(sqrt_price_x96.full_mul(sqrt_price_x96), U512::one() << 192)?;
+    atomic_price.checked_mul(&Decimal::new(
+        U256::exp10(decimals0 as usize),
+        decimals1 as u32,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn price_of_v2_pair() {
+        // 10M USDC (6 decimals) and 5000 WETH (18 decimals)
+        let reserve_usdc = U256::from(10_000_000_000_000u64);
+        let reserve_weth = U256::exp10(21) * 5;
+        assert_eq!(
+            price_from_reserves(reserve_weth, reserve_usdc, 18, 6).unwrap(),
+            Decimal::from_str("2000").unwrap()
+        );
+        assert_eq!(
+            price_from_reserves(reserve_usdc, reserve_weth, 6, 18).unwrap(),
+            Decimal::from_str("0.0005").unwrap()
+        );
+        assert_eq!(price_from_reserves(U256::zero(), reserve_weth, 6, 18), None);
+    }
+
+    #[test]
+    fn price_of_v3_pool() {
+        // an atomic price of 4 has the square root 2 * 2^96
+        let sqrt_price_x96 = U256::from(2) << 96;
+        assert_eq!(
+            price_from_sqrt_price_x96(sqrt_price_x96, 18, 18).unwrap(),
+            Decimal::from_str("4").unwrap()
+        );
+        // token0 with 6 and token1 with 18 decimals, i.e. a whole token0 is worth 4 * 10^-12
+        assert_eq!(
+            price_from_sqrt_price_x96(sqrt_price_x96, 6, 18).unwrap(),
+            Decimal::from_str("0.000000000004").unwrap()
+        );
+    }
+}
@@ -0,0 +1,162 @@
+//! Index of the tokens of all auctions, which is used to find auctions by the symbol or
+//! address of their auctioning or bidding token.
+
+use model::auction_details::AuctionDetails;
+use primitive_types::H160;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    /// Auctions by the lowercase symbols of their tokens.
+    symbols: BTreeMap<String, HashSet<u64>>,
+    /// Auctions by the lowercase hex addresses of their tokens, without `0x` prefix.
+    addresses: BTreeMap<String, HashSet<u64>>,
+    /// Indexed symbols and addresses of each auction, for removing them again.
+    keys: HashMap<u64, (Vec<String>, Vec<String>)>,
+}
+
+impl SearchIndex {
+    /// Indexes the tokens of the auction, replacing its previously indexed tokens.
+    pub fn insert(&mut self, details: &AuctionDetails) {
+        let auction_id = details.auction_id;
+        self.remove(auction_id);
+        let symbols = vec![
+            details.symbol_auctioning_token.to_lowercase(),
+            details.symbol_bidding_token.to_lowercase(),
+        ];
+        let addresses = vec![
+            address_key(details.address_auctioning_token),
+            address_key(details.address_bidding_token),
+        ];
+        for symbol in symbols.iter().filter(|symbol| !symbol.is_empty()) {
+            self.symbols
+                .entry(symbol.clone())
+                .or_default()
+                .insert(auction_id);
+        }
+        for address in &addresses {
+            self.addresses
+                .entry(address.clone())
+                .or_default()
+                .insert(auction_id);
+        }
+        self.keys.insert(auction_id, (symbols, addresses));
+    }
+
+    pub fn remove(&mut self, auction_id: u64) {
+        let (symbols, addresses) = match self.keys.remove(&auction_id) {
+            Some(keys) => keys,
+            None => return,
+        };
+        for symbol in symbols {
+            remove_from(&mut self.symbols, symbol, auction_id);
+        }
+        for address in addresses {
+            remove_from(&mut self.addresses, address, auction_id);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Auctions with a token, whose symbol contains the query case-insensitively or whose
+    /// address starts with the query.
+    pub fn search(&self, query: &str) -> HashSet<u64> {
+        let query = query.trim().to_lowercase();
+        let mut auction_ids = HashSet::new();
+        if query.is_empty() {
+            return auction_ids;
+        }
+        for (symbol, ids) in &self.symbols {
+            if symbol.contains(&query) {
+                auction_ids.extend(ids);
+            }
+        }
+        let address_prefix = query.strip_prefix("0x").unwrap_or(&query);
+        if !address_prefix.is_empty() && address_prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+            for (_, ids) in self
+                .addresses
+                .range(address_prefix.to_string()..)
+                .take_while(|(address, _)| address.starts_with(address_prefix))
+            {
+                auction_ids.extend(ids);
+            }
+        }
+        auction_ids
+    }
+}
+
+fn address_key(address: H160) -> String {
+    hex::encode(address.as_bytes())
+}
+
+fn remove_from(map: &mut BTreeMap<String, HashSet<u64>>, key: String, auction_id: u64) {
+    if let Some(ids) = map.get_mut(&key) {
+        ids.remove(&auction_id);
+        if ids.is_empty() {
+            map.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auction(auction_id: u64, auctioning: &str, bidding: &str, address: u64) -> AuctionDetails {
+        AuctionDetails {
+            auction_id,
+            symbol_auctioning_token: auctioning.to_string(),
+            symbol_bidding_token: bidding.to_string(),
+            address_auctioning_token: H160::from_low_u64_be(address),
+            address_bidding_token: H160::from_low_u64_be(0xdead),
+            ..Default::default()
+        }
+    }
+
+    fn sorted(ids: HashSet<u64>) -> Vec<u64> {
+        let mut ids: Vec<u64> = ids.into_iter().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    #[test]
+    fn matches_symbols_case_insensitively() {
+        let mut index = SearchIndex::default();
+        index.insert(&auction(1, "GNO", "WETH", 1));
+        index.insert(&auction(2, "wxDAI", "USDC", 2));
+        assert_eq!(sorted(index.search("gno")), vec![1]);
+        assert_eq!(sorted(index.search("Dai")), vec![2]);
+        assert_eq!(sorted(index.search("w")), vec![1, 2]);
+        assert!(index.search("").is_empty());
+    }
+
+    #[test]
+    fn matches_address_prefixes() {
+        let mut index = SearchIndex::default();
+        index.insert(&auction(1, "A", "B", 0x1234));
+        index.insert(&auction(2, "C", "D", 0x5678));
+        let address = format!("{:?}", H160::from_low_u64_be(0x1234));
+        assert_eq!(sorted(index.search(&address)), vec![1]);
+        assert_eq!(sorted(index.search(&address[..30])), vec![1, 2]);
+        assert_eq!(sorted(index.search(&address.to_uppercase()[2..])), vec![1]);
+        assert_eq!(
+            sorted(index.search(&format!("{:?}", H160::from_low_u64_be(0xdead)))),
+            vec![1, 2]
+        );
+    }
+
+    #[test]
+    fn replaces_and_removes_auctions() {
+        let mut index = SearchIndex::default();
+        index.insert(&auction(1, "GNO", "WETH", 1));
+        index.insert(&auction(1, "COW", "WETH", 1));
+        assert!(index.search("gno").is_empty());
+        assert_eq!(sorted(index.search("cow")), vec![1]);
+        index.remove(1);
+        assert!(index.search("weth").is_empty());
+        assert!(index.symbols.is_empty());
+        assert!(index.addresses.is_empty());
+    }
+}
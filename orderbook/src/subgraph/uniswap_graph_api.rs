@@ -1,18 +1,15 @@
-//! Module containing The Graph API client used for retrieving Balancer weighted
-//! pools from the Balancer V2 subgraph.
-//!
-//! The pools retrieved from this client are used to prime the graph event store
-//! to reduce start-up time. We do not use this in general for retrieving pools
-//! as to:
-//! - not rely on external services
-//! - ensure that we are using the latest up-to-date pool data by using events
-//!   from the node
+//! Module containing The Graph API client used for retrieving historical token prices
+//! from the daily reserves of Uniswap V2 pairs.
 
 use super::thegraph::SubgraphClient;
-use anyhow::anyhow;
-use anyhow::{bail, Result};
+use crate::price_oracle::PriceOracle;
+use anyhow::{anyhow, Result};
+use ethcontract::Address;
+use futures::future::{BoxFuture, FutureExt};
+use model::decimal::Decimal;
 use serde_json::json;
 use std::collections::HashMap;
+use tokio::sync::Mutex;
 
 #[macro_export]
 macro_rules! json_map {
@@ -25,35 +22,37 @@ macro_rules! json_map {
         map
     }}
 }
-/// A client to the Uniswap V2 subgraph.
+/// A client to a Uniswap V2 subgraph, which reads the USD price of a token from the
+/// daily reserves of a pair, in which the token is traded against a USD stable coin.
 ///
 /// This client is not implemented to allow general GraphQL queries, but instead
 /// implements high-level methods that perform GraphQL queries under the hood.
 pub struct UniswapSubgraphClient {
     client: SubgraphClient,
-    request_store: HashMap<u64, f64>,
+    token: Address,
+    pair: Address,
+    request_store: Mutex<HashMap<u64, Decimal>>,
 }
 
 impl UniswapSubgraphClient {
-    /// Creates a new Balancer subgraph client for the specified chain ID.
-    pub fn for_chain(chain_id: u64) -> Result<Self> {
-        let subgraph_name = match chain_id {
-            1 => "uniswap-v2",
-            4 => "uniswap-rinkeby-v2",
-            _ => bail!("unsupported chain {}", chain_id),
-        };
+    /// Creates a new client for the subgraph `org/name`, pricing `token` with the reserves
+    /// of `pair`.
+    pub fn new(org: &str, name: &str, token: Address, pair: Address) -> Result<Self> {
         Ok(Self {
-            client: SubgraphClient::new("uniswap", subgraph_name)?,
-            request_store: HashMap::new(),
+            client: SubgraphClient::new(org, name)?,
+            token,
+            pair,
+            request_store: Mutex::new(HashMap::new()),
         })
     }
 
-    /// Retrieves the list of registered pools from the subgraph.
-    pub async fn get_eth_usd_price(&mut self, timestamp: u64) -> Result<f64> {
+    /// Retrieves the USD price of the token at the day of the timestamp.
+    pub async fn get_usd_price(&self, timestamp: u64) -> Result<Decimal> {
         let sec_per_day = (24 * 60 * 60) as u64;
         let div = timestamp / sec_per_day;
         let timestamp_of_day = div * sec_per_day;
-        if let Some(price) = self.request_store.get(&timestamp_of_day) {
+        let mut request_store = self.request_store.lock().await;
+        if let Some(price) = request_store.get(&timestamp_of_day) {
             Ok(*price)
         } else {
             let amm = self
@@ -62,13 +61,16 @@ impl UniswapSubgraphClient {
                     price_query::QUERY,
                     Some(json_map! {
                         "date" => json!(timestamp_of_day),
+                        "pair" => json!(self.pair),
                     }),
                 )
                 .await?
                 .pair_day_datas;
             if let Some(amm_ratio) = amm.get(0) {
-                let price = amm_ratio.reserve0 / amm_ratio.reserve1;
-                self.request_store.insert(timestamp_of_day, price);
+                let price = amm_ratio
+                    .price_of(self.token)
+                    .ok_or_else(|| anyhow!("Invalid reserves in the api response"))?;
+                request_store.insert(timestamp_of_day, price);
                 Ok(price)
             } else {
                 Err(anyhow!(
@@ -79,20 +81,46 @@ impl UniswapSubgraphClient {
     }
 }
 
+impl PriceOracle for UniswapSubgraphClient {
+    fn get_usd_price(
+        &self,
+        token: Address,
+        timestamp: u64,
+    ) -> BoxFuture<'_, Result<Option<Decimal>>> {
+        async move {
+            if token != self.token {
+                return Ok(None);
+            }
+            Ok(Some(
+                UniswapSubgraphClient::get_usd_price(self, timestamp).await?,
+            ))
+        }
+        .boxed()
+    }
+
+    fn is_historical(&self) -> bool {
+        true
+    }
+}
+
 mod price_query {
 
-    use anyhow::Result;
-    use serde::de::{Deserializer, Error as _};
+    use ethcontract::Address;
+    use model::decimal::Decimal;
     use serde::Deserialize;
 
-    use std::borrow::Cow;
-
     pub const QUERY: &str = r#"
-        query PairDayData($date: Int) {
+        query PairDayData($date: Int, $pair: Bytes) {
             pairDayDatas(
                 first: 1,
-                where: { date: $date, pairAddress: "0xb4e16d0168e52d35cacd2c6185b44281ec28c9dc" }
+                where: { date: $date, pairAddress: $pair }
             ) {
+                token0 {
+                    id
+                }
+                token1 {
+                    id
+                }
                 reserve0
                 reserve1
               }
@@ -108,32 +136,48 @@ mod price_query {
     #[derive(Debug, Deserialize, PartialEq)]
     #[serde(rename_all = "camelCase")]
     pub struct AmmRatio {
-        #[serde(deserialize_with = "deserialize_decimal_f64")]
-        pub reserve0: f64,
-        #[serde(deserialize_with = "deserialize_decimal_f64")]
-        pub reserve1: f64,
+        pub token0: Token,
+        pub token1: Token,
+        pub reserve0: Decimal,
+        pub reserve1: Decimal,
     }
-    pub fn deserialize_decimal_f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        let decimal_str = Cow::<str>::deserialize(deserializer)?;
-        decimal_str.parse::<f64>().map_err(D::Error::custom)
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    pub struct Token {
+        pub id: Address,
+    }
+
+    impl AmmRatio {
+        /// Price of the token in units of the other token of the pair.
+        pub fn price_of(&self, token: Address) -> Option<Decimal> {
+            if token == self.token0.id {
+                self.reserve1.checked_div(&self.reserve0)
+            } else if token == self.token1.id {
+                self.reserve0.checked_div(&self.reserve1)
+            } else {
+                None
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::str::FromStr;
 
     #[test]
     fn decode_amm_data() {
         use price_query::*;
 
+        let usdc = Address::from_str("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+        let weth = Address::from_str("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap();
         let data = Data {
             pair_day_datas: vec![AmmRatio {
-                reserve0: 156_588_648.020_372_78_f64,
-                reserve1: 1.430_946_749_699_242_f64,
+                token0: Token { id: usdc },
+                token1: Token { id: weth },
+                reserve0: Decimal::from_str("156588648.02037278").unwrap(),
+                reserve1: Decimal::from_str("1.430946749699242").unwrap(),
             }],
         };
         assert_eq!(
@@ -141,6 +185,8 @@ mod tests {
                 {
                       "pairDayDatas": [
                         {
+                          "token0": { "id": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48" },
+                          "token1": { "id": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2" },
                           "reserve0": "156588648.02037278",
                           "reserve1": "1.430946749699242"
                         }
@@ -152,12 +198,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn prices_either_token_of_the_pair() {
+        use price_query::*;
+
+        let token0 = Address::from_low_u64_be(1);
+        let token1 = Address::from_low_u64_be(2);
+        let amm_ratio = AmmRatio {
+            token0: Token { id: token0 },
+            token1: Token { id: token1 },
+            reserve0: Decimal::from_str("4000").unwrap(),
+            reserve1: Decimal::from_str("2").unwrap(),
+        };
+        assert_eq!(
+            amm_ratio.price_of(token0),
+            Some(Decimal::from_str("0.0005").unwrap())
+        );
+        assert_eq!(
+            amm_ratio.price_of(token1),
+            Some(Decimal::from_str("2000").unwrap())
+        );
+        assert_eq!(amm_ratio.price_of(Address::from_low_u64_be(3)), None);
+    }
+
     #[tokio::test(flavor = "current_thread")]
     #[ignore]
     async fn uniswap_price_subgraph_query() {
-        let mut client = UniswapSubgraphClient::for_chain(1).unwrap();
+        let client = UniswapSubgraphClient::new(
+            "uniswap",
+            "uniswap-v2",
+            Address::from_str("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap(),
+            Address::from_str("b4e16d0168e52d35cacd2c6185b44281ec28c9dc").unwrap(),
+        )
+        .unwrap();
         let timestamp = 1625260016u64;
-        let response = client.get_eth_usd_price(timestamp).await;
+        let response = client.get_usd_price(timestamp).await;
         println!("{:?}", response);
     }
 }
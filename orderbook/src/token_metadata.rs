@@ -0,0 +1,265 @@
+//! Metadata of the tokens traded in auctions.
+//!
+//! Tokens are queried with raw calls instead of the generated ERC20 binding, as many tokens
+//! deviate from the standard: e.g. MKR returns its symbol as `bytes32`, and `name()` or even
+//! `decimals()` are optional. Reverting calls and missing or undecodable values fall back to
+//! defaults instead of failing the synchronization of the auction. Failures of the node itself
+//! are returned as errors, so that the auction is retried instead of keeping the defaults.
+
+use anyhow::{Context, Result};
+use ethabi::{ParamType, Token};
+use ethcontract::Address;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use web3::types::{Bytes, CallRequest};
+use web3::Web3;
+
+/// Decimals assumed for tokens without a `decimals()` method.
+pub const DEFAULT_DECIMALS: u8 = 18;
+/// Symbol of tokens without a readable `symbol()` method.
+pub const UNKNOWN_SYMBOL: &str = "UNKNOWN";
+
+const SYMBOL_SELECTOR: [u8; 4] = [0x95, 0xd8, 0x9b, 0x41];
+const NAME_SELECTOR: [u8; 4] = [0x06, 0xfd, 0xde, 0x03];
+const DECIMALS_SELECTOR: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenMetadata {
+    pub symbol: String,
+    pub name: String,
+    pub decimals: u8,
+    #[serde(rename = "logoURI", default)]
+    pub logo_uri: Option<String>,
+}
+
+/// Token metadata of one chain, which is fetched once per token and then cached.
+pub struct TokenMetadataRegistry {
+    web3: Web3<web3::transports::Http>,
+    /// Metadata from a token list, which takes precedence over the on-chain metadata.
+    overrides: HashMap<Address, TokenMetadata>,
+    cache: Mutex<HashMap<Address, TokenMetadata>>,
+}
+
+impl TokenMetadataRegistry {
+    pub fn new(
+        web3: Web3<web3::transports::Http>,
+        overrides: HashMap<Address, TokenMetadata>,
+    ) -> Self {
+        Self {
+            web3,
+            overrides,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn get(&self, token: Address) -> Result<TokenMetadata> {
+        if let Some(metadata) = self.overrides.get(&token) {
+            return Ok(metadata.clone());
+        }
+        let cached = self.cache.lock().unwrap().get(&token).cloned();
+        if let Some(metadata) = cached {
+            return Ok(metadata);
+        }
+        let symbol = self
+            .call(token, SYMBOL_SELECTOR)
+            .await?
+            .and_then(|data| decode_string(&data));
+        let name = self
+            .call(token, NAME_SELECTOR)
+            .await?
+            .and_then(|data| decode_string(&data));
+        let decimals = self
+            .call(token, DECIMALS_SELECTOR)
+            .await?
+            .and_then(|data| decode_decimals(&data));
+        if symbol.is_none() || decimals.is_none() {
+            tracing::warn!(
+                "token {:?} has a non-standard symbol or decimals, using defaults",
+                token
+            );
+        }
+        let metadata = TokenMetadata {
+            symbol: symbol.unwrap_or_else(|| UNKNOWN_SYMBOL.to_string()),
+            name: name.unwrap_or_default(),
+            decimals: decimals.unwrap_or(DEFAULT_DECIMALS),
+            logo_uri: None,
+        };
+        self.cache.lock().unwrap().insert(token, metadata.clone());
+        Ok(metadata)
+    }
+
+    /// Returns the data returned by calling the method without arguments, or None if the
+    /// call reverted. Other failures of the call are returned as errors.
+    async fn call(&self, token: Address, selector: [u8; 4]) -> Result<Option<Vec<u8>>> {
+        let request = CallRequest {
+            to: Some(token),
+            data: Some(Bytes(selector.to_vec())),
+            ..Default::default()
+        };
+        match self.web3.eth().call(request, None).await {
+            Ok(data) => Ok(Some(data.0)),
+            Err(web3::Error::Rpc(err)) if is_revert_message(&err.message) => {
+                tracing::debug!("calling token {:?} reverted: {:?}", token, err);
+                Ok(None)
+            }
+            Err(err) => Err(err).with_context(|| format!("calling token {:?} failed", token)),
+        }
+    }
+}
+
+/// Whether the node rejected a call because its execution reverted. Nodes report reverts with
+/// different messages, e.g. "execution reverted" or "VM execution error.".
+fn is_revert_message(message: &str) -> bool {
+    let message = message.to_lowercase();
+    ["revert", "vm execution error", "invalid opcode"]
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+/// Decodes a `string` or a zero-padded `bytes32` return value.
+fn decode_string(data: &[u8]) -> Option<String> {
+    let value = match ethabi::decode(&[ParamType::String], data) {
+        Ok(tokens) => match tokens.into_iter().next() {
+            Some(Token::String(value)) => value,
+            _ => return None,
+        },
+        Err(_) if data.len() == 32 => {
+            let length = data.iter().position(|byte| *byte == 0).unwrap_or(32);
+            String::from_utf8(data[..length].to_vec()).ok()?
+        }
+        Err(_) => return None,
+    };
+    let value = value.trim_matches(char::from(0)).trim().to_string();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+fn decode_decimals(data: &[u8]) -> Option<u8> {
+    match ethabi::decode(&[ParamType::Uint(256)], data)
+        .ok()?
+        .into_iter()
+        .next()
+    {
+        Some(Token::Uint(decimals)) if decimals <= 255.into() => Some(decimals.as_u32() as u8),
+        _ => None,
+    }
+}
+
+/// Token list in the format of https://tokenlists.org.
+#[derive(Deserialize)]
+struct TokenList {
+    tokens: Vec<TokenListEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenListEntry {
+    chain_id: u32,
+    address: Address,
+    #[serde(flatten)]
+    metadata: TokenMetadata,
+}
+
+/// Loads the metadata of the tokens of the chain from a token list file.
+pub fn load_token_list(path: &Path, chain_id: u32) -> Result<HashMap<Address, TokenMetadata>> {
+    let token_list = fs::read_to_string(path)
+        .with_context(|| format!("reading token list {} failed", path.display()))?;
+    parse_token_list(&token_list, chain_id)
+        .with_context(|| format!("parsing token list {} failed", path.display()))
+}
+
+pub fn parse_token_list(
+    token_list: &str,
+    chain_id: u32,
+) -> Result<HashMap<Address, TokenMetadata>> {
+    let token_list: TokenList = serde_json::from_str(token_list)?;
+    Ok(token_list
+        .tokens
+        .into_iter()
+        .filter(|entry| entry.chain_id == chain_id)
+        .map(|entry| (entry.address, entry.metadata))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn decodes_string_symbols() {
+        let data = ethabi::encode(&[Token::String("GNO".to_string())]);
+        assert_eq!(decode_string(&data), Some("GNO".to_string()));
+    }
+
+    #[test]
+    fn decodes_bytes32_symbols() {
+        let mut data = [0u8; 32];
+        data[..3].copy_from_slice(b"MKR");
+        assert_eq!(decode_string(&data), Some("MKR".to_string()));
+        assert_eq!(decode_string(&[0u8; 32]), None);
+        assert_eq!(decode_string(&[]), None);
+    }
+
+    #[test]
+    fn decodes_decimals() {
+        let data = ethabi::encode(&[Token::Uint(18.into())]);
+        assert_eq!(decode_decimals(&data), Some(18));
+        let data = ethabi::encode(&[Token::Uint(256.into())]);
+        assert_eq!(decode_decimals(&data), None);
+        assert_eq!(decode_decimals(&[]), None);
+    }
+
+    #[test]
+    fn detects_reverts() {
+        assert!(is_revert_message("execution reverted"));
+        assert!(is_revert_message("VM execution error."));
+        assert!(is_revert_message("invalid opcode: INVALID"));
+        assert!(!is_revert_message("header not found"));
+        assert!(!is_revert_message(
+            "daily request count exceeded, request rate limited"
+        ));
+    }
+
+    #[test]
+    fn parses_tokens_of_the_chain_from_token_list() {
+        let token_list = json!({
+            "name": "Test list",
+            "tokens": [
+                {
+                    "chainId": 1,
+                    "address": "0x6810e776880C02933D47DB1b9fc05908e5386b96",
+                    "name": "Gnosis Token",
+                    "symbol": "GNO",
+                    "decimals": 18,
+                    "logoURI": "https://example.com/gno.png"
+                },
+                {
+                    "chainId": 100,
+                    "address": "0x9C58BAcC331c9aa871AFD802DB6379a98e80CEdb",
+                    "name": "Gnosis Token on xDai",
+                    "symbol": "GNO",
+                    "decimals": 18
+                }
+            ]
+        });
+        let tokens = parse_token_list(&token_list.to_string(), 1).unwrap();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(
+            tokens[&"6810e776880C02933D47DB1b9fc05908e5386b96".parse().unwrap()],
+            TokenMetadata {
+                symbol: "GNO".to_string(),
+                name: "Gnosis Token".to_string(),
+                decimals: 18,
+                logo_uri: Some("https://example.com/gno.png".to_string()),
+            }
+        );
+    }
+}
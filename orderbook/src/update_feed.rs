@@ -0,0 +1,133 @@
+//! Broadcasting of applied orderbook changes to the clients of the streaming API.
+
+use model::order::Order;
+use primitive_types::U256;
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+// Clients that fall behind by more updates are notified that they missed some.
+const CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuctionUpdate {
+    pub auction_id: u64,
+    /// Block up to which the orderbook was synced when the update was applied.
+    pub block_number: u64,
+    #[serde(flatten)]
+    pub event: AuctionEvent,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AuctionEvent {
+    NewOrder {
+        order: Order,
+    },
+    OrderCancellation {
+        order: Order,
+    },
+    OrderClaim {
+        order: Order,
+    },
+    #[serde(rename_all = "camelCase")]
+    ClearingPriceUpdate {
+        clearing_order: Order,
+        volume_clearing_order: U256,
+        bidding_amount: U256,
+    },
+    /// Updates after `block_number` were reverted due to a reorg, hence clients should
+    /// reload the orderbook of the auction.
+    Reorg,
+}
+
+#[derive(Debug)]
+pub struct UpdateFeed {
+    sender: broadcast::Sender<AuctionUpdate>,
+}
+
+impl Default for UpdateFeed {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl UpdateFeed {
+    pub fn publish(&self, update: AuctionUpdate) {
+        // Sending only fails if there are currently no subscribers.
+        let _ = self.sender.send(update);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<AuctionUpdate> {
+        self.sender.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn serialize_auction_update() {
+        let order = Order {
+            sell_amount: U256::from_dec_str("1230").unwrap(),
+            buy_amount: U256::from_dec_str("123").unwrap(),
+            user_id: 10_u64,
+        };
+        assert_eq!(
+            serde_json::to_value(AuctionUpdate {
+                auction_id: 1,
+                block_number: 12,
+                event: AuctionEvent::NewOrder { order },
+            })
+            .unwrap(),
+            json!({
+                "auctionId": 1,
+                "blockNumber": 12,
+                "type": "newOrder",
+                "order": "0x000000000000000a00000000000000000000007b0000000000000000000004ce",
+            }),
+        );
+        assert_eq!(
+            serde_json::to_value(AuctionUpdate {
+                auction_id: 1,
+                block_number: 12,
+                event: AuctionEvent::ClearingPriceUpdate {
+                    clearing_order: order,
+                    volume_clearing_order: U256::from(2),
+                    bidding_amount: U256::from(3),
+                },
+            })
+            .unwrap(),
+            json!({
+                "auctionId": 1,
+                "blockNumber": 12,
+                "type": "clearingPriceUpdate",
+                "clearingOrder": "0x000000000000000a00000000000000000000007b0000000000000000000004ce",
+                "volumeClearingOrder": "0x2",
+                "biddingAmount": "0x3",
+            }),
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn subscribers_receive_published_updates() {
+        let feed = UpdateFeed::default();
+        // publishing without subscribers is fine
+        feed.publish(AuctionUpdate {
+            auction_id: 1,
+            block_number: 11,
+            event: AuctionEvent::Reorg,
+        });
+        let mut receiver = feed.subscribe();
+        let update = AuctionUpdate {
+            auction_id: 1,
+            block_number: 12,
+            event: AuctionEvent::Reorg,
+        };
+        feed.publish(update.clone());
+        assert_eq!(receiver.recv().await.unwrap(), update);
+    }
+}